@@ -0,0 +1,78 @@
+//! Compares the cost of appending a block to [`LocalChain`]s of different depths.
+//!
+//! Run with `cargo bench -p bdk_chain --bench local_chain_append`. Appending should cost roughly
+//! the same regardless of how many blocks the chain already holds.
+
+use std::time::{Duration, Instant};
+
+use bdk_chain::{
+    bitcoin::{hashes::Hash, BlockHash},
+    local_chain::{CheckPoint, LocalChain},
+    BlockId,
+};
+
+/// The chain depths to compare.
+const DEPTHS: [u32; 3] = [1_000, 100_000, 700_000];
+
+/// The number of blocks appended (and timed) per chain.
+const APPENDS: u32 = 1_000;
+
+fn block(height: u32) -> BlockId {
+    BlockId {
+        height,
+        hash: BlockHash::hash(&height.to_le_bytes()),
+    }
+}
+
+fn chain_of_depth(depth: u32) -> LocalChain {
+    let tip = CheckPoint::from_block_ids((0..depth).map(block)).expect("heights are ascending");
+    LocalChain::from_tip(tip).expect("has genesis")
+}
+
+/// Times `APPENDS` calls of `append`, each given the chain and the height of the block to append.
+fn time_appends(depth: u32, mut append: impl FnMut(&mut LocalChain, u32)) -> Duration {
+    let mut chain = chain_of_depth(depth);
+    let start = Instant::now();
+    for height in depth..depth + APPENDS {
+        append(&mut chain, height);
+    }
+    start.elapsed() / APPENDS
+}
+
+fn run() {
+    println!(
+        "{:>8} {:>15} {:>15} {:>15}",
+        "depth", "apply_update", "insert_block", "apply_changeset"
+    );
+    for depth in DEPTHS {
+        let apply_update = time_appends(depth, |chain, height| {
+            let update = chain.tip().push(block(height)).expect("is above tip");
+            chain
+                .apply_update(update.into_update(false))
+                .expect("update must connect");
+        });
+        let insert_block = time_appends(depth, |chain, height| {
+            chain.insert_block(block(height)).expect("is above tip");
+        });
+        let apply_changeset = time_appends(depth, |chain, height| {
+            chain
+                .apply_changeset(&[(height, Some(block(height).hash))].into())
+                .expect("is above tip");
+        });
+        println!(
+            "{:>8} {:>15?} {:>15?} {:>15?}",
+            depth, apply_update, insert_block, apply_changeset
+        );
+    }
+}
+
+fn main() {
+    // Dropping a checkpoint list recurses once per checkpoint, which overflows the default stack
+    // of the main thread for the deepest chains.
+    std::thread::Builder::new()
+        .stack_size(1 << 30)
+        .spawn(run)
+        .expect("must spawn")
+        .join()
+        .expect("must not panic");
+}
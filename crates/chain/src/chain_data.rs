@@ -1,10 +1,18 @@
+use alloc::string::String;
 use bitcoin::{hashes::Hash, BlockHash, OutPoint, TxOut, Txid};
 
-use crate::{Anchor, AnchorFromBlockPosition, COINBASE_MATURITY};
+use crate::{Anchor, AnchorFromBlockPosition, AnchorKind, COINBASE_MATURITY};
 
 /// Represents the observed position of some chain data.
 ///
 /// The generic `A` should be a [`Anchor`] implementation.
+///
+/// With the `serde` feature, human-readable formats (such as JSON) (de)serialize a [`ChainPosition`]
+/// as a flat object with a `"status"` field. A confirmed position is `{"status": "confirmed", ...}`
+/// where the remaining fields are the fields of the anchor (so `A` must be serialized as a struct or
+/// map), and an unconfirmed position is `{"status": "unconfirmed", "last_seen": <unix seconds>}`.
+/// Other formats (such as bincode) cannot deserialize this flat object without it describing
+/// itself, so they use the externally tagged `Confirmed(A)` / `Unconfirmed(u64)` variants instead.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, core::hash::Hash)]
 pub enum ChainPosition<A> {
     /// The chain data is seen as confirmed, and in anchored by `A`.
@@ -13,11 +21,188 @@ pub enum ChainPosition<A> {
     Unconfirmed(u64),
 }
 
+/// A timestamp in seconds since the unix epoch.
+///
+/// This documents the unit of timestamps such as the last-seen time of unconfirmed chain data.
+/// It converts to and from a raw `u64` of unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, core::hash::Hash, Default)]
+pub struct UnixSeconds(pub u64);
+
+impl UnixSeconds {
+    /// Get the timestamp as seconds since the unix epoch.
+    pub fn as_secs(&self) -> u64 {
+        self.0
+    }
+
+    /// Get the current time from the system clock ([`std::time::SystemTime`]).
+    ///
+    /// A system time before the unix epoch results in a timestamp of `0`.
+    #[cfg(feature = "std")]
+    pub fn now() -> Self {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        Self(secs)
+    }
+}
+
+impl From<u64> for UnixSeconds {
+    fn from(secs: u64) -> Self {
+        Self(secs)
+    }
+}
+
+impl From<UnixSeconds> for u64 {
+    fn from(secs: UnixSeconds) -> Self {
+        secs.0
+    }
+}
+
+/// The serde representation of [`ChainPosition`] for human-readable formats.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(crate = "serde_crate", tag = "status", rename_all = "lowercase")]
+enum ChainPositionRepr<A> {
+    Confirmed(A),
+    Unconfirmed { last_seen: u64 },
+}
+
+/// The serde representation of [`ChainPosition`] for formats which are not human-readable.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(crate = "serde_crate", rename = "ChainPosition")]
+enum ChainPositionBinaryRepr<A> {
+    Confirmed(A),
+    Unconfirmed(u64),
+}
+
+#[cfg(feature = "serde")]
+impl<A: serde::Serialize> serde::Serialize for ChainPosition<A> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return match self {
+                ChainPosition::Confirmed(a) => ChainPositionBinaryRepr::Confirmed(a),
+                ChainPosition::Unconfirmed(last_seen) => {
+                    ChainPositionBinaryRepr::Unconfirmed(*last_seen)
+                }
+            }
+            .serialize(serializer);
+        }
+        match self {
+            ChainPosition::Confirmed(a) => ChainPositionRepr::Confirmed(a),
+            ChainPosition::Unconfirmed(last_seen) => ChainPositionRepr::Unconfirmed {
+                last_seen: *last_seen,
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, A: serde::Deserialize<'de>> serde::Deserialize<'de> for ChainPosition<A> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Ok(match ChainPositionBinaryRepr::deserialize(deserializer)? {
+                ChainPositionBinaryRepr::Confirmed(a) => ChainPosition::Confirmed(a),
+                ChainPositionBinaryRepr::Unconfirmed(last_seen) => {
+                    ChainPosition::Unconfirmed(last_seen)
+                }
+            });
+        }
+        Ok(match ChainPositionRepr::deserialize(deserializer)? {
+            ChainPositionRepr::Confirmed(a) => ChainPosition::Confirmed(a),
+            ChainPositionRepr::Unconfirmed { last_seen } => ChainPosition::Unconfirmed(last_seen),
+        })
+    }
+}
+
 impl<A> ChainPosition<A> {
+    /// Construct an unconfirmed variant using the given `last_seen` time in unix seconds.
+    pub fn unconfirmed(last_seen: impl Into<UnixSeconds>) -> Self {
+        Self::Unconfirmed(last_seen.into().as_secs())
+    }
+
+    /// Construct an unconfirmed variant which is last seen at the current time.
+    ///
+    /// The current time is read from the system clock (refer to [`UnixSeconds::now`]).
+    #[cfg(feature = "std")]
+    pub fn unconfirmed_now() -> Self {
+        Self::unconfirmed(UnixSeconds::now())
+    }
+
     /// Returns whether [`ChainPosition`] is confirmed or not.
     pub fn is_confirmed(&self) -> bool {
         matches!(self, Self::Confirmed(_))
     }
+
+    /// Get a reference to the anchor if the position is confirmed, or `None` otherwise.
+    pub fn anchor(&self) -> Option<&A> {
+        match self {
+            ChainPosition::Confirmed(a) => Some(a),
+            ChainPosition::Unconfirmed(_) => None,
+        }
+    }
+
+    /// Bumps the last-seen timestamp of an unconfirmed position to `seen_at`.
+    ///
+    /// The timestamp is only ever moved forward, so sightings processed out of order will not
+    /// lower it. This is a no-op for the confirmed variant.
+    pub fn bump_last_seen(&mut self, seen_at: u64) {
+        if let Self::Unconfirmed(last_seen) = self {
+            *last_seen = (*last_seen).max(seen_at);
+        }
+    }
+
+    /// Demotes the position to unconfirmed, for example when the confirmation is orphaned by a
+    /// reorg.
+    ///
+    /// A confirmed position becomes unconfirmed with the given `last_seen`. An unconfirmed position
+    /// stays unconfirmed with the maximum of its current last-seen timestamp and `last_seen`, so the
+    /// original sighting time is never lowered.
+    pub fn unconfirm(self, last_seen: u64) -> ChainPosition<A> {
+        match self {
+            ChainPosition::Confirmed(_) => ChainPosition::Unconfirmed(last_seen),
+            ChainPosition::Unconfirmed(prev_last_seen) => {
+                ChainPosition::Unconfirmed(prev_last_seen.max(last_seen))
+            }
+        }
+    }
+
+    /// Returns the [`ConfirmedPosition`] if the position is confirmed, or `None` otherwise.
+    ///
+    /// This allows filtering an iterator of positions to confirmed ones with
+    /// `filter_map(ChainPosition::confirmed)`.
+    pub fn confirmed(self) -> Option<ConfirmedPosition<A>> {
+        match self {
+            ChainPosition::Confirmed(a) => Some(ConfirmedPosition(a)),
+            ChainPosition::Unconfirmed(_) => None,
+        }
+    }
+}
+
+/// A [`ChainPosition`] that is guaranteed to be confirmed.
+///
+/// This is obtained with [`ChainPosition::confirmed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, core::hash::Hash)]
+pub struct ConfirmedPosition<A>(A);
+
+impl<A> ConfirmedPosition<A> {
+    /// Get a reference to the anchor of the confirmed position.
+    pub fn anchor(&self) -> &A {
+        &self.0
+    }
+
+    /// Consumes the confirmed position and returns the anchor.
+    pub fn into_anchor(self) -> A {
+        self.0
+    }
+}
+
+impl<A> From<ConfirmedPosition<A>> for ChainPosition<A> {
+    fn from(confirmed: ConfirmedPosition<A>) -> Self {
+        ChainPosition::Confirmed(confirmed.0)
+    }
 }
 
 impl<A: Clone> ChainPosition<&A> {
@@ -38,6 +223,204 @@ impl<A: Anchor> ChainPosition<A> {
             ChainPosition::Unconfirmed(_) => None,
         }
     }
+
+    /// Returns a key that can be used to order [`ChainPosition`]s without requiring `A: Ord`.
+    ///
+    /// The key layout is stable and is one of:
+    ///
+    /// * `(0, height)` for confirmed positions, where `height` is the
+    ///   [`confirmation_height_upper_bound`] of the anchor.
+    /// * `(1, u64::MAX - last_seen)` for unconfirmed positions.
+    ///
+    /// This means confirmed positions sort before unconfirmed positions, confirmed positions sort by
+    /// ascending height and unconfirmed positions sort with the most recently seen first.
+    ///
+    /// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+    pub fn ordering_key(&self) -> (u8, u64) {
+        match self {
+            ChainPosition::Confirmed(a) => (0, a.confirmation_height_upper_bound() as u64),
+            ChainPosition::Unconfirmed(last_seen) => (1, u64::MAX - last_seen),
+        }
+    }
+
+    /// Returns whether `self` and `other` represent the same confirmation.
+    ///
+    /// Confirmed positions are compared only by their [`confirmation_height_upper_bound`], so
+    /// positions anchored to different anchor blocks are considered the same if their
+    /// confirmation heights match. Unconfirmed positions are compared by their last-seen
+    /// timestamp.
+    ///
+    /// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+    pub fn same_confirmation(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ChainPosition::Confirmed(a), ChainPosition::Confirmed(b)) => {
+                a.confirmation_height_upper_bound() == b.confirmation_height_upper_bound()
+            }
+            (ChainPosition::Unconfirmed(a), ChainPosition::Unconfirmed(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns a label describing the position with chain `tip`, for display purposes.
+    ///
+    /// This is `"Confirmed (N)"` for confirmed positions, where `N` is the number of confirmations
+    /// (derived from the [`confirmation_height_upper_bound`], so a position at the tip has one
+    /// confirmation), and `"Unconfirmed"` for unconfirmed positions.
+    ///
+    /// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+    pub fn status_label(&self, tip: u32) -> String {
+        match self {
+            ChainPosition::Confirmed(a) => format!(
+                "Confirmed ({})",
+                confirmation_count(a.confirmation_height_upper_bound(), tip)
+            ),
+            ChainPosition::Unconfirmed(_) => String::from("Unconfirmed"),
+        }
+    }
+
+    /// Classifies the position with chain `tip` into a [`FinalityBucket`].
+    ///
+    /// The number of confirmations is derived from the [`confirmation_height_upper_bound`] (so a
+    /// position at the tip has one confirmation), and the thresholds are:
+    ///
+    /// * [`FinalityBucket::Unconfirmed`]: unconfirmed, or confirmed above `tip` (no confirmations).
+    /// * [`FinalityBucket::Low`]: 1 to 2 confirmations.
+    /// * [`FinalityBucket::Medium`]: 3 to 5 confirmations.
+    /// * [`FinalityBucket::Final`]: 6 or more confirmations.
+    ///
+    /// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+    pub fn finality_bucket(&self, tip: u32) -> FinalityBucket {
+        let confirmations = match self {
+            ChainPosition::Confirmed(a) => {
+                confirmation_count(a.confirmation_height_upper_bound(), tip)
+            }
+            ChainPosition::Unconfirmed(_) => 0,
+        };
+        match confirmations {
+            0 => FinalityBucket::Unconfirmed,
+            1..=2 => FinalityBucket::Low,
+            3..=5 => FinalityBucket::Medium,
+            _ => FinalityBucket::Final,
+        }
+    }
+
+    /// Returns whichever of `self` and `other` is the most deeply confirmed position.
+    ///
+    /// A confirmed position is preferred over an unconfirmed position. Between two confirmed
+    /// positions, the one with the lower [`confirmation_height_upper_bound`] is preferred. Between
+    /// two unconfirmed positions, the one with the higher last-seen timestamp is preferred. If both
+    /// positions are equally preferred, `self` is returned.
+    ///
+    /// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+    pub fn deepest(self, other: Self) -> Self {
+        if other.is_deeper_than(&self) {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// Whether `self` is strictly preferred over `other` as per [`deepest`].
+    ///
+    /// [`deepest`]: Self::deepest
+    fn is_deeper_than(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ChainPosition::Confirmed(a), ChainPosition::Confirmed(b)) => {
+                a.confirmation_height_upper_bound() < b.confirmation_height_upper_bound()
+            }
+            (ChainPosition::Confirmed(_), ChainPosition::Unconfirmed(_)) => true,
+            (ChainPosition::Unconfirmed(_), ChainPosition::Confirmed(_)) => false,
+            (ChainPosition::Unconfirmed(a), ChainPosition::Unconfirmed(b)) => a > b,
+        }
+    }
+}
+
+impl ChainPosition<ConfirmationTimeHeightAnchor> {
+    /// Returns whether the position is confirmed with a `confirmation_time` within the half-open
+    /// interval `[start, end)`.
+    ///
+    /// `start` is inclusive and `end` is exclusive, so consecutive windows (e.g. months) never
+    /// both contain the same position. All times are in unix seconds. Unconfirmed positions are
+    /// never within the window.
+    pub fn confirmed_in_window(&self, start: u64, end: u64) -> bool {
+        match self {
+            ChainPosition::Confirmed(a) => (start..end).contains(&a.confirmation_time),
+            ChainPosition::Unconfirmed(_) => false,
+        }
+    }
+}
+
+/// How final a [`ChainPosition`] is, as returned by [`ChainPosition::finality_bucket`].
+///
+/// Buckets are ordered from the least to the most final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, core::hash::Hash)]
+pub enum FinalityBucket {
+    /// The chain data has no confirmations.
+    Unconfirmed,
+    /// The chain data has 1 to 2 confirmations.
+    Low,
+    /// The chain data has 3 to 5 confirmations.
+    Medium,
+    /// The chain data has 6 or more confirmations.
+    Final,
+}
+
+/// Represents the observed position of some chain data, where unconfirmed data may expire.
+///
+/// This is the same as [`ChainPosition`], except that the unconfirmed variant also records when
+/// the chain data should be considered stale (e.g. to garbage-collect transactions that were
+/// evicted from the mempool). Existing [`ChainPosition`]s can be converted with [`From`], where
+/// [`ChainPosition::Unconfirmed`] becomes [`MempoolPosition::Unconfirmed`] without an expiry.
+/// Converting back into a [`ChainPosition`] drops the expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, core::hash::Hash)]
+pub enum MempoolPosition<A> {
+    /// The chain data is seen as confirmed, and in anchored by `A`.
+    Confirmed(A),
+    /// The chain data is seen in mempool.
+    Unconfirmed {
+        /// The last-seen timestamp in unix seconds.
+        last_seen: u64,
+        /// The timestamp (in unix seconds) at which the chain data expires (if any).
+        expires_at: Option<u64>,
+    },
+}
+
+impl<A> MempoolPosition<A> {
+    /// Returns whether [`MempoolPosition`] is confirmed or not.
+    pub fn is_confirmed(&self) -> bool {
+        matches!(self, Self::Confirmed(_))
+    }
+
+    /// Returns whether the position is unconfirmed and has expired at the time `now`.
+    ///
+    /// Confirmed positions and unconfirmed positions without an expiry never expire.
+    pub fn is_expired(&self, now: u64) -> bool {
+        match self {
+            Self::Confirmed(_) => false,
+            Self::Unconfirmed { expires_at, .. } => expires_at.map_or(false, |t| t <= now),
+        }
+    }
+}
+
+impl<A> From<ChainPosition<A>> for MempoolPosition<A> {
+    fn from(chain_position: ChainPosition<A>) -> Self {
+        match chain_position {
+            ChainPosition::Confirmed(a) => Self::Confirmed(a),
+            ChainPosition::Unconfirmed(last_seen) => Self::Unconfirmed {
+                last_seen,
+                expires_at: None,
+            },
+        }
+    }
+}
+
+impl<A> From<MempoolPosition<A>> for ChainPosition<A> {
+    fn from(mempool_position: MempoolPosition<A>) -> Self {
+        match mempool_position {
+            MempoolPosition::Confirmed(a) => Self::Confirmed(a),
+            MempoolPosition::Unconfirmed { last_seen, .. } => Self::Unconfirmed(last_seen),
+        }
+    }
 }
 
 /// Block height and timestamp at which a transaction is confirmed.
@@ -64,14 +447,76 @@ pub enum ConfirmationTime {
 
 impl ConfirmationTime {
     /// Construct an unconfirmed variant using the given `last_seen` time in unix seconds.
-    pub fn unconfirmed(last_seen: u64) -> Self {
-        Self::Unconfirmed { last_seen }
+    pub fn unconfirmed(last_seen: impl Into<UnixSeconds>) -> Self {
+        Self::Unconfirmed {
+            last_seen: last_seen.into().as_secs(),
+        }
+    }
+
+    /// Construct an unconfirmed variant which is last seen at the current time.
+    ///
+    /// The current time is read from the system clock (refer to [`UnixSeconds::now`]).
+    #[cfg(feature = "std")]
+    pub fn unconfirmed_now() -> Self {
+        Self::unconfirmed(UnixSeconds::now())
     }
 
     /// Returns whether [`ConfirmationTime`] is the confirmed variant.
     pub fn is_confirmed(&self) -> bool {
         matches!(self, Self::Confirmed { .. })
     }
+
+    /// Compares by recency, where the most recent [`ConfirmationTime`] is ordered first.
+    ///
+    /// Unconfirmed variants are ordered before confirmed variants. Unconfirmed variants are ordered
+    /// by descending `last_seen`, and confirmed variants are ordered by descending `height` (then by
+    /// descending `time`).
+    ///
+    /// This is the ordering typically used to display a list of wallet transactions (pending
+    /// transactions on top). Use the derived [`Ord`] implementation for structural comparisons.
+    pub fn recency_cmp(&self, other: &Self) -> core::cmp::Ordering {
+        use core::cmp::Ordering;
+        match (self, other) {
+            (Self::Unconfirmed { last_seen: a }, Self::Unconfirmed { last_seen: b }) => b.cmp(a),
+            (Self::Unconfirmed { .. }, Self::Confirmed { .. }) => Ordering::Less,
+            (Self::Confirmed { .. }, Self::Unconfirmed { .. }) => Ordering::Greater,
+            (
+                Self::Confirmed {
+                    height: height_a,
+                    time: time_a,
+                },
+                Self::Confirmed {
+                    height: height_b,
+                    time: time_b,
+                },
+            ) => (height_b, time_b).cmp(&(height_a, time_a)),
+        }
+    }
+
+    /// Reconstructs the [`ChainPosition`] that this [`ConfirmationTime`] was converted from.
+    ///
+    /// [`ConfirmationTime`] does not record the block hash of the confirmation block, so it has to
+    /// be supplied as `anchor_hash`. A confirmed variant is anchored to the block of `anchor_hash`
+    /// at the confirmation height. Converting the result back into a [`ConfirmationTime`] results
+    /// in `self`.
+    pub fn into_chain_position(
+        self,
+        anchor_hash: BlockHash,
+    ) -> ChainPosition<ConfirmationTimeHeightAnchor> {
+        match self {
+            Self::Confirmed { height, time } => {
+                ChainPosition::Confirmed(ConfirmationTimeHeightAnchor {
+                    anchor_block: BlockId {
+                        height,
+                        hash: anchor_hash,
+                    },
+                    confirmation_height: height,
+                    confirmation_time: time,
+                })
+            }
+            Self::Unconfirmed { last_seen } => ChainPosition::Unconfirmed(last_seen),
+        }
+    }
 }
 
 impl From<ChainPosition<ConfirmationTimeHeightAnchor>> for ConfirmationTime {
@@ -86,6 +531,45 @@ impl From<ChainPosition<ConfirmationTimeHeightAnchor>> for ConfirmationTime {
     }
 }
 
+/// Extension methods for iterators of [`ChainPosition`]s.
+pub trait ChainPositionIterExt:
+    Iterator<Item = ChainPosition<ConfirmationTimeHeightAnchor>>
+{
+    /// Convert each [`ChainPosition`] into the [`ConfirmationTime`] it represents.
+    fn into_confirmation_times(self) -> ConfirmationTimes<Self>
+    where
+        Self: Sized,
+    {
+        ConfirmationTimes(self)
+    }
+}
+
+impl<I> ChainPositionIterExt for I where
+    I: Iterator<Item = ChainPosition<ConfirmationTimeHeightAnchor>>
+{
+}
+
+/// An iterator that converts [`ChainPosition`]s into [`ConfirmationTime`]s.
+///
+/// This is created by [`ChainPositionIterExt::into_confirmation_times`].
+#[derive(Debug, Clone)]
+pub struct ConfirmationTimes<I>(I);
+
+impl<I> Iterator for ConfirmationTimes<I>
+where
+    I: Iterator<Item = ChainPosition<ConfirmationTimeHeightAnchor>>,
+{
+    type Item = ConfirmationTime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(ConfirmationTime::from)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
 /// A reference to a block in the canonical chain.
 ///
 /// `BlockId` implements [`Anchor`]. When a transaction is anchored to `BlockId`, the confirmation
@@ -103,6 +587,38 @@ pub struct BlockId {
     pub hash: BlockHash,
 }
 
+impl BlockId {
+    /// Construct the [`BlockId`] of the given `block` at `height`.
+    pub fn from_block(block: &bitcoin::Block, height: u32) -> Self {
+        Self {
+            height,
+            hash: block.block_hash(),
+        }
+    }
+
+    /// Returns a copy of this [`BlockId`] with the `height` replaced.
+    pub fn with_height(self, height: u32) -> BlockId {
+        BlockId { height, ..self }
+    }
+
+    /// Returns whether this block is at the height directly above `other`.
+    ///
+    /// Note that this only compares heights and does not check that the blocks are linked.
+    pub fn succeeds(&self, other: &BlockId) -> bool {
+        other.height.checked_add(1) == Some(self.height)
+    }
+
+    /// Compares by `height` only, ignoring the `hash`.
+    ///
+    /// Unlike the derived [`Ord`] implementation (which orders blocks of the same height by hash),
+    /// this returns [`Ordering::Equal`] for competing blocks at the same height.
+    ///
+    /// [`Ordering::Equal`]: core::cmp::Ordering::Equal
+    pub fn cmp_height(&self, other: &BlockId) -> core::cmp::Ordering {
+        self.height.cmp(&other.height)
+    }
+}
+
 impl Anchor for BlockId {
     fn anchor_block(&self) -> Self {
         *self
@@ -130,6 +646,12 @@ impl From<(u32, BlockHash)> for BlockId {
     }
 }
 
+impl From<(&bitcoin::Block, u32)> for BlockId {
+    fn from((block, height): (&bitcoin::Block, u32)) -> Self {
+        Self::from_block(block, height)
+    }
+}
+
 impl From<BlockId> for (u32, BlockHash) {
     fn from(block_id: BlockId) -> Self {
         (block_id.height, block_id.hash)
@@ -174,6 +696,9 @@ impl Anchor for ConfirmationHeightAnchor {
     fn confirmation_height_upper_bound(&self) -> u32 {
         self.confirmation_height
     }
+    fn kind(&self) -> AnchorKind {
+        AnchorKind::Height
+    }
 }
 
 impl AnchorFromBlockPosition for ConfirmationHeightAnchor {
@@ -185,6 +710,12 @@ impl AnchorFromBlockPosition for ConfirmationHeightAnchor {
     }
 }
 
+impl From<ConfirmationHeightAnchor> for BlockId {
+    fn from(anchor: ConfirmationHeightAnchor) -> Self {
+        anchor.anchor_block
+    }
+}
+
 /// An [`Anchor`] implementation that also records the exact confirmation time and height of the
 /// transaction.
 ///
@@ -206,6 +737,18 @@ pub struct ConfirmationTimeHeightAnchor {
     pub confirmation_time: u64,
 }
 
+impl ConfirmationTimeHeightAnchor {
+    /// Returns the time elapsed since confirmation, relative to the reference time `now`.
+    ///
+    /// Both `now` and the returned value are in unix seconds (the same unit as
+    /// [`confirmation_time`]). This returns `0` if `now` is before the confirmation time.
+    ///
+    /// [`confirmation_time`]: Self::confirmation_time
+    pub fn age(&self, now: u64) -> u64 {
+        now.saturating_sub(self.confirmation_time)
+    }
+}
+
 impl Anchor for ConfirmationTimeHeightAnchor {
     fn anchor_block(&self) -> BlockId {
         self.anchor_block
@@ -214,6 +757,9 @@ impl Anchor for ConfirmationTimeHeightAnchor {
     fn confirmation_height_upper_bound(&self) -> u32 {
         self.confirmation_height
     }
+    fn kind(&self) -> AnchorKind {
+        AnchorKind::TimeHeight
+    }
 }
 
 impl AnchorFromBlockPosition for ConfirmationTimeHeightAnchor {
@@ -226,8 +772,32 @@ impl AnchorFromBlockPosition for ConfirmationTimeHeightAnchor {
     }
 }
 
+impl From<ConfirmationTimeHeightAnchor> for ConfirmationHeightAnchor {
+    fn from(anchor: ConfirmationTimeHeightAnchor) -> Self {
+        Self {
+            anchor_block: anchor.anchor_block,
+            confirmation_height: anchor.confirmation_height,
+        }
+    }
+}
+
+impl From<ConfirmationTimeHeightAnchor> for BlockId {
+    fn from(anchor: ConfirmationTimeHeightAnchor) -> Self {
+        anchor.anchor_block
+    }
+}
+
 /// A `TxOut` with as much data as we can retrieve about it
+///
+/// The derived [`Ord`] is a structural total order over all fields, so two records of the same
+/// `outpoint` (e.g. with different chain positions) are not considered equal. Use
+/// [`cmp_by_outpoint`](Self::cmp_by_outpoint) to order by outpoint only.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
 pub struct FullTxOut<A> {
     /// The location of the `TxOut`.
     pub outpoint: OutPoint,
@@ -241,6 +811,42 @@ pub struct FullTxOut<A> {
     pub is_on_coinbase: bool,
 }
 
+impl<A> FullTxOut<A> {
+    /// Construct a [`FullTxOut`] of the given `outpoint`, `txout` and `chain_position`.
+    ///
+    /// The output is assumed to be unspent and not on a coinbase transaction. Use
+    /// [`with_spent_by`] and [`with_coinbase`] to set these.
+    ///
+    /// [`with_spent_by`]: Self::with_spent_by
+    /// [`with_coinbase`]: Self::with_coinbase
+    pub fn new(outpoint: OutPoint, txout: TxOut, chain_position: ChainPosition<A>) -> Self {
+        Self {
+            outpoint,
+            txout,
+            chain_position,
+            spent_by: None,
+            is_on_coinbase: false,
+        }
+    }
+
+    /// Set the chain position and txid of the transaction that spends this output.
+    pub fn with_spent_by(mut self, chain_position: ChainPosition<A>, txid: Txid) -> Self {
+        self.spent_by = Some((chain_position, txid));
+        self
+    }
+
+    /// Set whether this output is on a coinbase transaction.
+    pub fn with_coinbase(mut self, is_on_coinbase: bool) -> Self {
+        self.is_on_coinbase = is_on_coinbase;
+        self
+    }
+
+    /// Compares by `outpoint` only, ignoring all other fields.
+    pub fn cmp_by_outpoint(&self, other: &Self) -> core::cmp::Ordering {
+        self.outpoint.cmp(&other.outpoint)
+    }
+}
+
 impl<A: Anchor> FullTxOut<A> {
     /// Whether the `txout` is considered mature.
     ///
@@ -298,4 +904,130 @@ impl<A: Anchor> FullTxOut<A> {
 
         true
     }
+
+    /// Whether the `txout` has at least `required_confirmations` confirmations with chain `tip`.
+    ///
+    /// This can be used to check whether a relative timelock (`OP_CHECKSEQUENCEVERIFY`) of
+    /// `required_confirmations` blocks is satisfied. Only the block-based form of relative timelocks
+    /// is handled, not the time-based form. An unconfirmed `txout` never satisfies the timelock.
+    ///
+    /// Depending on the implementation of [`confirmation_height_upper_bound`] in [`Anchor`], this
+    /// method may return false-negatives. In other words, interpreted confirmation count may be
+    /// less than the actual value.
+    ///
+    /// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+    pub fn relative_height_satisfied(&self, tip: u32, required_confirmations: u32) -> bool {
+        let confirmation_height = match &self.chain_position {
+            ChainPosition::Confirmed(anchor) => anchor.confirmation_height_upper_bound(),
+            ChainPosition::Unconfirmed(_) => return false,
+        };
+        confirmation_count(confirmation_height, tip) >= required_confirmations
+    }
+
+    /// Get the anchor block of the `txout` if it is confirmed, or `None` otherwise.
+    ///
+    /// Refer to [`Anchor::anchor_block`].
+    pub fn confirmation_block(&self) -> Option<BlockId> {
+        self.chain_position.anchor().map(|a| a.anchor_block())
+    }
+
+    /// Get the [`SpendStatus`] of the `txout` with chain `tip`.
+    ///
+    /// The status only depends on [`spent_by`]. The number of confirmations of a confirmed spend is
+    /// derived from the spending transaction's [`confirmation_height_upper_bound`] (so a spend at
+    /// the tip has one confirmation, and a spend anchored above the tip has none).
+    ///
+    /// The chain position of the `txout` itself is not considered, so an unconfirmed `txout` that
+    /// has a spend is reported as spent (the spending transaction is unconfirmed as well in a
+    /// consistent view of the chain).
+    ///
+    /// [`spent_by`]: Self::spent_by
+    /// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+    pub fn spend_status(&self, tip: u32) -> SpendStatus {
+        match &self.spent_by {
+            None => SpendStatus::Unspent,
+            Some((ChainPosition::Unconfirmed(_), txid)) => SpendStatus::SpentUnconfirmed(*txid),
+            Some((ChainPosition::Confirmed(anchor), txid)) => SpendStatus::SpentConfirmed {
+                txid: *txid,
+                confirmations: confirmation_count(anchor.confirmation_height_upper_bound(), tip),
+            },
+        }
+    }
+}
+
+/// The spend state of a [`FullTxOut`], as returned by [`FullTxOut::spend_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, core::hash::Hash)]
+pub enum SpendStatus {
+    /// The output is not spent.
+    Unspent,
+    /// The output is spent by the unconfirmed transaction of this txid.
+    SpentUnconfirmed(Txid),
+    /// The output is spent by a confirmed transaction.
+    SpentConfirmed {
+        /// The txid of the spending transaction.
+        txid: Txid,
+        /// The number of confirmations of the spending transaction.
+        confirmations: u32,
+    },
+}
+
+/// The number of confirmations of a block at `confirmation_height` with chain `tip`.
+///
+/// A block at the tip has one confirmation, and a block above the tip has none.
+fn confirmation_count(confirmation_height: u32, tip: u32) -> u32 {
+    match tip.checked_sub(confirmation_height) {
+        Some(age) => age + 1,
+        None => 0,
+    }
+}
+
+/// Resolves which of two conflicting spends of the same output should be trusted.
+///
+/// Each spend is a `(ChainPosition, Txid)` tuple, in the same shape as [`FullTxOut::spent_by`].
+/// A confirmed spend is preferred over an unconfirmed spend. Between two confirmed spends, the one
+/// with the lower [`confirmation_height_upper_bound`] is preferred. Between two unconfirmed spends,
+/// the one with the higher last-seen timestamp is preferred. If both spends are equally preferred,
+/// `a` is returned.
+///
+/// [`confirmation_height_upper_bound`]: Anchor::confirmation_height_upper_bound
+pub fn resolve_conflicting_spends<A: Anchor>(
+    a: (ChainPosition<A>, Txid),
+    b: (ChainPosition<A>, Txid),
+) -> (ChainPosition<A>, Txid) {
+    if b.0.is_deeper_than(&a.0) {
+        b
+    } else {
+        a
+    }
+}
+
+/// Sums the values of the `utxos` that are spendable with chain `tip`.
+///
+/// A utxo is considered spendable if [`FullTxOut::is_confirmed_and_spendable`] returns `true`
+/// (i.e. it is mature, confirmed and unspent within `tip`).
+pub fn spendable_balance<A: Anchor>(
+    utxos: impl IntoIterator<Item = FullTxOut<A>>,
+    tip: u32,
+) -> bitcoin::Amount {
+    let sats = utxos
+        .into_iter()
+        .filter(|utxo| utxo.is_confirmed_and_spendable(tip))
+        .map(|utxo| utxo.txout.value)
+        .sum();
+    bitcoin::Amount::from_sat(sats)
+}
+
+/// Sums the values of the `utxos` that are confirmed.
+///
+/// A utxo is considered confirmed if its [`ChainPosition::is_confirmed`] returns `true`. Unlike
+/// [`spendable_balance`], this does not take maturity or spends into account.
+pub fn confirmed_balance<A: Anchor>(
+    utxos: impl IntoIterator<Item = FullTxOut<A>>,
+) -> bitcoin::Amount {
+    let sats = utxos
+        .into_iter()
+        .filter(|utxo| utxo.chain_position.is_confirmed())
+        .map(|utxo| utxo.txout.value)
+        .sum();
+    bitcoin::Amount::from_sat(sats)
 }
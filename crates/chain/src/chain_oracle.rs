@@ -1,4 +1,5 @@
 use crate::BlockId;
+use alloc::vec::Vec;
 
 /// Represents a service that tracks the blockchain.
 ///
@@ -23,3 +24,22 @@ pub trait ChainOracle {
     /// Get the best chain's chain tip.
     fn get_chain_tip(&self) -> Result<BlockId, Self::Error>;
 }
+
+/// Filters `claims` to the blocks that the `oracle` determines to be in the chain of `tip`.
+///
+/// A block is kept only if [`ChainOracle::is_block_in_chain`] returns `Some(true)`. Blocks that
+/// the `oracle` cannot determine (`None`) are discarded along with blocks that are not in the
+/// chain (`Some(false)`). The order of `claims` is preserved.
+pub fn filter_canonical<O: ChainOracle>(
+    oracle: &O,
+    tip: BlockId,
+    claims: impl IntoIterator<Item = BlockId>,
+) -> Result<Vec<BlockId>, O::Error> {
+    let mut canonical = Vec::new();
+    for block in claims {
+        if oracle.is_block_in_chain(block, tip)? == Some(true) {
+            canonical.push(block);
+        }
+    }
+    Ok(canonical)
+}
@@ -1,11 +1,15 @@
 //! The [`LocalChain`] is a local implementation of [`ChainOracle`].
 
 use core::convert::Infallible;
+use core::ops::{Bound, RangeInclusive};
 
 use crate::collections::BTreeMap;
-use crate::{BlockId, ChainOracle};
+use crate::{Anchor, Append, BlockId, ChainOracle};
+use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use bitcoin::block::Header;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
 use bitcoin::BlockHash;
 
 /// The [`ChangeSet`] represents changes to [`LocalChain`].
@@ -14,6 +18,62 @@ use bitcoin::BlockHash;
 /// (if [`Some`]), or removing a [`CheckPoint`] (if [`None`]).
 pub type ChangeSet = BTreeMap<u32, Option<BlockHash>>;
 
+/// Summarizes the `changeset` in a human-readable form for logging.
+///
+/// Heights of added and removed blocks are grouped into ranges of consecutive heights, for example:
+/// `added 3 blocks (800142..800144), removed 1 block (800141)`. Replaced blocks are counted as
+/// added. An empty `changeset` is summarized as `no changes`.
+pub fn summarize_changeset(changeset: &ChangeSet) -> String {
+    let (added, removed): (Vec<_>, Vec<_>) = changeset.iter().partition(|(_, hash)| hash.is_some());
+
+    let mut parts = Vec::new();
+    if !added.is_empty() {
+        parts.push(format!(
+            "added {}",
+            summarize_heights(added.into_iter().map(|(&h, _)| h))
+        ));
+    }
+    if !removed.is_empty() {
+        parts.push(format!(
+            "removed {}",
+            summarize_heights(removed.into_iter().map(|(&h, _)| h))
+        ));
+    }
+    if parts.is_empty() {
+        return String::from("no changes");
+    }
+    parts.join(", ")
+}
+
+/// Describes ascending `heights` as a block count and ranges of consecutive heights.
+fn summarize_heights(heights: impl IntoIterator<Item = u32>) -> String {
+    let mut count = 0_usize;
+    let mut ranges = Vec::<(u32, u32)>::new();
+    for height in heights {
+        count += 1;
+        match ranges.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(height) => *end = height,
+            _ => ranges.push((height, height)),
+        }
+    }
+    let ranges = ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                format!("{}", start)
+            } else {
+                format!("{}..{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{} block{} ({})",
+        count,
+        if count == 1 { "" } else { "s" },
+        ranges.join(", ")
+    )
+}
+
 /// A [`LocalChain`] checkpoint is used to find the agreement point between two chains and as a
 /// transaction anchor.
 ///
@@ -22,9 +82,34 @@ pub type ChangeSet = BTreeMap<u32, Option<BlockHash>>;
 /// Internally, checkpoints are nodes of a reference-counted linked-list. This allows the caller to
 /// cheaply clone a [`CheckPoint`] without copying the whole list and to view the entire chain
 /// without holding a lock on [`LocalChain`].
+///
+/// Two checkpoints are considered equal if they have the same [`BlockId`], regardless of the
+/// checkpoints that come before them. Checkpoints are ordered by height first, and ties (competing
+/// blocks of the same height) are broken by block hash. This is consistent with the ordering of
+/// [`BlockId`].
 #[derive(Debug, Clone)]
 pub struct CheckPoint(Arc<CPInner>);
 
+impl PartialEq for CheckPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.block_id() == other.block_id()
+    }
+}
+
+impl Eq for CheckPoint {}
+
+impl PartialOrd for CheckPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CheckPoint {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.block_id().cmp(&other.block_id())
+    }
+}
+
 /// The internal contents of [`CheckPoint`].
 #[derive(Debug, Clone)]
 struct CPInner {
@@ -62,6 +147,36 @@ impl CheckPoint {
         Ok(acc)
     }
 
+    /// Construct a checkpoint from a list of `(height, hash)` pairs in ascending height order.
+    ///
+    /// This is the inverse of [`to_pairs`].
+    ///
+    /// # Errors
+    ///
+    /// This fails under the same conditions as [`from_block_ids`], with the same error type.
+    ///
+    /// [`to_pairs`]: Self::to_pairs
+    /// [`from_block_ids`]: Self::from_block_ids
+    pub fn from_pairs(pairs: Vec<(u32, BlockHash)>) -> Result<Self, Option<Self>> {
+        Self::from_block_ids(pairs.into_iter().map(BlockId::from))
+    }
+
+    /// Returns the `(height, hash)` pairs of this checkpoint and all checkpoints below it, in
+    /// ascending height order.
+    ///
+    /// This is a compact representation of the checkpoint list which can be converted back with
+    /// [`from_pairs`].
+    ///
+    /// [`from_pairs`]: Self::from_pairs
+    pub fn to_pairs(&self) -> Vec<(u32, BlockHash)> {
+        let mut pairs = self
+            .iter()
+            .map(|cp| (cp.height(), cp.hash()))
+            .collect::<Vec<_>>();
+        pairs.reverse();
+        pairs
+    }
+
     /// Construct a checkpoint from the given `header` and block `height`.
     ///
     /// If `header` is of the genesis block, the checkpoint won't have a [`prev`] node. Otherwise,
@@ -124,6 +239,25 @@ impl CheckPoint {
         Ok(curr)
     }
 
+    /// Extends the checkpoint linked list by a iterator of block ids, reporting the first block
+    /// which cannot be added.
+    ///
+    /// This is the same as [`extend`], except that the error contains the partially extended
+    /// checkpoint (up to but excluding the failing block) together with the first block which does
+    /// not have a greater height than the previous one.
+    ///
+    /// [`extend`]: Self::extend
+    pub fn try_extend(
+        self,
+        blocks: impl IntoIterator<Item = BlockId>,
+    ) -> Result<Self, (Self, BlockId)> {
+        let mut curr = self;
+        for block in blocks {
+            curr = curr.push(block).map_err(|cp| (cp, block))?;
+        }
+        Ok(curr)
+    }
+
     /// Get the [`BlockId`] of the checkpoint.
     pub fn block_id(&self) -> BlockId {
         self.0.block
@@ -144,10 +278,133 @@ impl CheckPoint {
         self.0.prev.clone().map(CheckPoint)
     }
 
+    /// Returns whether `self` and `other` are competing forks, meaning that they are at the same
+    /// height but with different block hashes.
+    pub fn is_competing_fork(&self, other: &CheckPoint) -> bool {
+        self.height() == other.height() && self.hash() != other.hash()
+    }
+
+    /// Get the checkpoint `n` nodes below this checkpoint, or `None` if the list has fewer than `n`
+    /// checkpoints below this one.
+    ///
+    /// Note that this walks back `n` checkpoints, which is only `n` blocks back if the checkpoints
+    /// are contiguous. `prev_n(0)` returns a clone of `self`.
+    pub fn prev_n(&self, n: u32) -> Option<CheckPoint> {
+        self.iter().nth(n as usize)
+    }
+
+    /// Get the block hash at `height` in this checkpoint list, if it exists.
+    ///
+    /// The list is walked by reference (without cloning any checkpoints) and the walk stops once
+    /// it is below `height`.
+    pub fn hash_at(&self, height: u32) -> Option<BlockHash> {
+        let mut current: &CPInner = &self.0;
+        loop {
+            if current.block.height == height {
+                return Some(current.block.hash);
+            }
+            if current.block.height < height {
+                return None;
+            }
+            current = current.prev.as_deref()?;
+        }
+    }
+
+    /// Get the last (lowest) checkpoint of this list.
+    ///
+    /// For a checkpoint of a [`LocalChain`], this is the genesis block. A partial checkpoint list
+    /// (such as that of an [`Update`]) may not reach genesis, in which case this is just its
+    /// lowest checkpoint. Refer to [`bottoms_out_at`] to check for a specific genesis block.
+    ///
+    /// This walks the entire list, so it is `O(n)`.
+    ///
+    /// [`bottoms_out_at`]: Self::bottoms_out_at
+    pub fn genesis(&self) -> CheckPoint {
+        let mut current = &self.0;
+        while let Some(prev) = &current.prev {
+            current = prev;
+        }
+        CheckPoint(current.clone())
+    }
+
+    /// Returns whether the last (lowest) checkpoint of this list is `genesis`.
+    ///
+    /// This is useful to verify that a full checkpoint list (e.g. one replicated from elsewhere)
+    /// really starts from the expected genesis block, rather than being truncated or belonging to
+    /// a different network.
+    pub fn bottoms_out_at(&self, genesis: BlockId) -> bool {
+        let mut current: &CPInner = &self.0;
+        while let Some(prev) = current.prev.as_deref() {
+            current = prev;
+        }
+        current.block == genesis
+    }
+
     /// Iterate from this checkpoint in descending height.
     pub fn iter(&self) -> CheckPointIter {
         self.clone().into_iter()
     }
+
+    /// Iterate over the [`BlockId`]s from this checkpoint in descending height.
+    ///
+    /// The list is walked by reference, so no checkpoints are cloned.
+    pub fn iter_block_ids(&self) -> impl Iterator<Item = BlockId> + '_ {
+        core::iter::successors(Some(&*self.0), |cp| cp.prev.as_deref()).map(|cp| cp.block)
+    }
+
+    /// Iterate from this checkpoint in descending height, yielding each checkpoint together with
+    /// its previous (lower) checkpoint (if any).
+    pub fn iter_with_prev(&self) -> impl Iterator<Item = (CheckPoint, Option<CheckPoint>)> {
+        self.iter().map(|cp| {
+            let prev = cp.prev();
+            (cp, prev)
+        })
+    }
+
+    /// Iterate from this checkpoint in descending height, while `f` returns `true`.
+    ///
+    /// The iteration stops at the first checkpoint for which `f` returns `false`, and that
+    /// checkpoint is not yielded.
+    pub fn walk_while<F: FnMut(&CheckPoint) -> bool>(
+        &self,
+        mut f: F,
+    ) -> impl Iterator<Item = CheckPoint> {
+        self.iter().take_while(move |cp| f(cp))
+    }
+
+    /// Estimates the number of heap bytes occupied by this checkpoint and all checkpoints below it.
+    ///
+    /// This only accounts for the nodes of the linked list (including [`Arc`] reference counts),
+    /// and is not exact. Nodes that are shared with other checkpoint lists are counted in full.
+    pub fn estimated_heap_bytes(&self) -> usize {
+        let node_bytes = core::mem::size_of::<CPInner>() + 2 * core::mem::size_of::<usize>();
+        self.iter().count() * node_bytes
+    }
+
+    /// Returns whether this checkpoint is the current tip of the given `chain`.
+    ///
+    /// This is the case if this checkpoint is the same node as the `chain`'s tip (a cheap pointer
+    /// comparison), or if this checkpoint's list contains exactly the same blocks as the `chain`.
+    /// In other words, a checkpoint that describes the chain's tip block but has different history
+    /// (e.g. a stale checkpoint from a previous sync) is not considered the tip.
+    pub fn is_tip_of(&self, chain: &LocalChain) -> bool {
+        let mut this_iter = self.iter();
+        let mut tip_iter = chain.iter_checkpoints();
+        loop {
+            match (this_iter.next(), tip_iter.next()) {
+                (Some(this), Some(tip)) => {
+                    if Arc::ptr_eq(&this.0, &tip.0) {
+                        return true;
+                    }
+                    if this != tip {
+                        return false;
+                    }
+                }
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
 }
 
 /// Iterates over checkpoints backwards.
@@ -201,11 +458,92 @@ pub struct Update {
     pub introduce_older_blocks: bool,
 }
 
+impl Update {
+    /// Construct an update from the given `header` and block `height`.
+    ///
+    /// This is the same as calling [`CheckPoint::from_header`] followed by
+    /// [`CheckPoint::into_update`]. `introduce_older_blocks` is `false`, as an update from a single
+    /// header is expected to build upon the previous tip (block-by-block syncing).
+    pub fn from_header(header: &Header, height: u32) -> Self {
+        CheckPoint::from_header(header, height).into_update(false)
+    }
+}
+
+/// A change to a [`LocalChain`], as reported by [`LocalChain::apply_update_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainEvent {
+    /// A block was added to the chain.
+    Added(BlockId),
+    /// A block was removed from the chain.
+    Removed(BlockId),
+}
+
+/// The changes to a [`LocalChain`] since a previously observed tip, as returned by
+/// [`LocalChain::tip_changed_since`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TipChange {
+    /// Blocks that were added since the previously observed tip, in ascending height order.
+    pub added: Vec<BlockId>,
+    /// Blocks of the previously observed tip that are no longer in the chain, in ascending height
+    /// order.
+    pub removed: Vec<BlockId>,
+}
+
 /// This is a local implementation of [`ChainOracle`].
 #[derive(Debug, Clone)]
 pub struct LocalChain {
     tip: CheckPoint,
     index: BTreeMap<u32, BlockHash>,
+    stats: ChainStats,
+    staged: Option<ChangeSet>,
+    trusted: BTreeMap<u32, BlockHash>,
+}
+
+/// The result of checking the integrity of a [`LocalChain`], as returned by
+/// [`LocalChain::integrity_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Whether the internal index contains exactly the blocks of the checkpoint list.
+    pub index_matches_tip: bool,
+    /// Whether both the internal index and the checkpoint list contain a genesis block.
+    pub genesis_present: bool,
+    /// Whether the chain has a block at every height from genesis to the tip.
+    ///
+    /// A chain which is not contiguous is still valid (most chains are sparse), so this is only
+    /// informational.
+    pub contiguous: bool,
+}
+
+/// The changes that applying an [`Update`] would make, as returned by
+/// [`LocalChain::report_update`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateReport {
+    /// The changes that applying the update would make.
+    pub changeset: ChangeSet,
+    /// Whether applying the update would invalidate (remove or replace) existing blocks.
+    pub would_reorg: bool,
+}
+
+impl IntegrityReport {
+    /// Whether the chain's internal state is consistent.
+    ///
+    /// This does not take [`contiguous`](Self::contiguous) into account.
+    pub fn is_ok(&self) -> bool {
+        self.index_matches_tip && self.genesis_present
+    }
+}
+
+/// Cumulative counters of the changes made to a [`LocalChain`], as returned by
+/// [`LocalChain::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChainStats {
+    /// The number of blocks added to the chain (including blocks which replaced another block).
+    pub blocks_added: u64,
+    /// The number of blocks removed from the chain (including blocks which were replaced by
+    /// another block).
+    pub blocks_removed: u64,
+    /// The number of reorgs, which are changes that remove or replace at least one block.
+    pub reorgs: u64,
 }
 
 impl PartialEq for LocalChain {
@@ -253,6 +591,13 @@ impl LocalChain {
         self.index.get(&0).copied().expect("must have genesis hash")
     }
 
+    /// Returns whether the chain's genesis hash is that of the given `network`.
+    ///
+    /// This is useful to check that the chain data is loaded for the expected network.
+    pub fn is_genesis_of(&self, network: bitcoin::Network) -> bool {
+        self.genesis_hash() == bitcoin::constants::genesis_block(network).block_hash()
+    }
+
     /// Construct [`LocalChain`] from genesis `hash`.
     #[must_use]
     pub fn from_genesis_hash(hash: BlockHash) -> (Self, ChangeSet) {
@@ -260,11 +605,36 @@ impl LocalChain {
         let chain = Self {
             tip: CheckPoint::new(BlockId { height, hash }),
             index: core::iter::once((height, hash)).collect(),
+            stats: ChainStats::default(),
+            staged: None,
+            trusted: BTreeMap::new(),
         };
         let changeset = chain.initial_changeset();
         (chain, changeset)
     }
 
+    /// Construct [`LocalChain`] from genesis `hash`, without returning the initial [`ChangeSet`].
+    ///
+    /// This is the same as [`from_genesis_hash`], for callers that do not need the initial
+    /// changeset (it can be derived later with [`initial_changeset`]).
+    ///
+    /// [`from_genesis_hash`]: Self::from_genesis_hash
+    /// [`initial_changeset`]: Self::initial_changeset
+    pub fn new_from_genesis(hash: BlockHash) -> Self {
+        let (chain, _) = Self::from_genesis_hash(hash);
+        chain
+    }
+
+    /// Construct a [`LocalChain`] from a given `tip` checkpoint.
+    ///
+    /// This is the same as [`from_tip`], and is provided for symmetry with [`new_from_genesis`].
+    ///
+    /// [`from_tip`]: Self::from_tip
+    /// [`new_from_genesis`]: Self::new_from_genesis
+    pub fn new_from_tip(tip: CheckPoint) -> Result<Self, MissingGenesisError> {
+        Self::from_tip(tip)
+    }
+
     /// Construct a [`LocalChain`] from an initial `changeset`.
     pub fn from_changeset(changeset: ChangeSet) -> Result<Self, MissingGenesisError> {
         let genesis_entry = changeset.get(&0).copied().flatten();
@@ -274,10 +644,12 @@ impl LocalChain {
         };
 
         let (mut chain, _) = Self::from_genesis_hash(genesis_hash);
-        chain.apply_changeset(&changeset)?;
+        chain.apply_changeset_unchecked(&changeset)?;
+        // constructing the chain does not count towards the stats
+        chain.stats = ChainStats::default();
 
         debug_assert!(chain._check_index_is_consistent_with_tip());
-        debug_assert!(chain._check_changeset_is_applied(&changeset));
+        debug_assert!(chain.is_applied(&changeset));
 
         Ok(chain)
     }
@@ -287,6 +659,9 @@ impl LocalChain {
         let mut chain = Self {
             tip,
             index: BTreeMap::new(),
+            stats: ChainStats::default(),
+            staged: None,
+            trusted: BTreeMap::new(),
         };
         chain.reindex(0);
 
@@ -324,6 +699,55 @@ impl LocalChain {
         let chain = Self {
             index: blocks,
             tip: tip.expect("already checked to have genesis"),
+            stats: ChainStats::default(),
+            staged: None,
+            trusted: BTreeMap::new(),
+        };
+
+        debug_assert!(chain._check_index_is_consistent_with_tip());
+        Ok(chain)
+    }
+
+    /// Constructs a [`LocalChain`] from a list of `(height, hash)` pairs which are already sorted in
+    /// strictly ascending height order.
+    ///
+    /// This is faster than [`from_blocks`] for large chains, as the checkpoint list is built in a
+    /// single pass over `blocks` (instead of first collecting them into a [`BTreeMap`]). The caller
+    /// must guarantee that the blocks are all of the same chain.
+    ///
+    /// # Errors
+    ///
+    /// [`FromBlocksSortedError::NotAscending`] occurs if `blocks` is not in strictly ascending
+    /// height order.
+    ///
+    /// [`FromBlocksSortedError::MissingGenesis`] occurs if `blocks` does not start with a genesis
+    /// block.
+    ///
+    /// [`from_blocks`]: Self::from_blocks
+    pub fn from_blocks_sorted(
+        blocks: Vec<(u32, BlockHash)>,
+    ) -> Result<Self, FromBlocksSortedError> {
+        if let Some(w) = blocks.windows(2).find(|w| w[0].0 >= w[1].0) {
+            return Err(FromBlocksSortedError::NotAscending { height: w[1].0 });
+        }
+        if blocks.first().map(|&(height, _)| height) != Some(0) {
+            return Err(FromBlocksSortedError::MissingGenesis(MissingGenesisError));
+        }
+
+        let mut prev: Option<Arc<CPInner>> = None;
+        for &block in &blocks {
+            prev = Some(Arc::new(CPInner {
+                block: BlockId::from(block),
+                prev,
+            }));
+        }
+
+        let chain = Self {
+            tip: CheckPoint(prev.expect("already checked to have genesis")),
+            index: blocks.into_iter().collect(),
+            stats: ChainStats::default(),
+            staged: None,
+            trusted: BTreeMap::new(),
         };
 
         debug_assert!(chain._check_index_is_consistent_with_tip());
@@ -335,6 +759,96 @@ impl LocalChain {
         self.tip.clone()
     }
 
+    /// Returns the highest height at which both `self` and `other` have the same block.
+    ///
+    /// Returns `None` if the genesis blocks of the chains differ.
+    pub fn common_height(&self, other: &LocalChain) -> Option<u32> {
+        if self.genesis_hash() != other.genesis_hash() {
+            return None;
+        }
+        self.index
+            .iter()
+            .rev()
+            .find(|(height, hash)| other.index.get(height) == Some(hash))
+            .map(|(&height, _)| height)
+    }
+
+    /// Returns how the chain has changed since `last_seen_tip` was observed, or `None` if it has not
+    /// changed.
+    ///
+    /// The changes are relative to the common ancestor, which is the highest block of
+    /// `last_seen_tip` that is still in the chain. Blocks of `last_seen_tip` above the common
+    /// ancestor are reported as removed, and blocks of the chain above the common ancestor are
+    /// reported as added. This returns `None` cheaply if `last_seen_tip` is the chain's current tip.
+    pub fn tip_changed_since(&self, last_seen_tip: &CheckPoint) -> Option<TipChange> {
+        if Arc::as_ptr(&self.tip.0) == Arc::as_ptr(&last_seen_tip.0) {
+            return None;
+        }
+
+        let mut removed = Vec::new();
+        let mut common_ancestor = None;
+        for cp in last_seen_tip.iter() {
+            if self.contains_block(cp.block_id()) {
+                common_ancestor = Some(cp.height());
+                break;
+            }
+            removed.push(cp.block_id());
+        }
+        removed.reverse();
+
+        let added = match common_ancestor {
+            Some(height) => self
+                .index
+                .range((Bound::Excluded(height), Bound::Unbounded)),
+            None => self.index.range(..),
+        }
+        .map(BlockId::from)
+        .collect::<Vec<_>>();
+
+        if added.is_empty() && removed.is_empty() {
+            return None;
+        }
+        Some(TipChange { added, removed })
+    }
+
+    /// Replaces the chain's tip with the given `tip` and re-derives the chain from it.
+    ///
+    /// This is useful to restore the chain to a previously captured tip (for example, the tip
+    /// returned by [`apply_update_returning_prev`]). The returned [`ChangeSet`] represents the
+    /// changes from the chain's state before this call.
+    ///
+    /// # Errors
+    ///
+    /// This will fail with [`ApplyChangeSetError::MissingGenesis`] if `tip` does not contain a
+    /// genesis checkpoint, with [`ApplyChangeSetError::GenesisAltered`] if the genesis checkpoint of
+    /// `tip` differs from that of `self`, and with [`ApplyChangeSetError::TrustedCheckpointViolation`]
+    /// if `tip` contradicts a trusted checkpoint. In all cases, `self` is left unchanged.
+    ///
+    /// [`apply_update_returning_prev`]: Self::apply_update_returning_prev
+    pub fn set_tip(&mut self, tip: CheckPoint) -> Result<ChangeSet, ApplyChangeSetError> {
+        let mut new_chain = Self::from_tip(tip).map_err(ApplyChangeSetError::MissingGenesis)?;
+
+        let mut changeset = ChangeSet::default();
+        for (&height, &hash) in &new_chain.index {
+            if self.index.get(&height) != Some(&hash) {
+                changeset.insert(height, Some(hash));
+            }
+        }
+        for &height in self.index.keys() {
+            if !new_chain.index.contains_key(&height) {
+                changeset.insert(height, None);
+            }
+        }
+        self.check_changeset(&changeset)?;
+
+        new_chain.stats = self.stats;
+        new_chain.staged = self.staged.take();
+        new_chain.trusted = core::mem::take(&mut self.trusted);
+        new_chain.record_changes(&self.index, &changeset);
+        *self = new_chain;
+        Ok(changeset)
+    }
+
     /// Applies the given `update` to the chain.
     ///
     /// The method returns [`ChangeSet`] on success. This represents the applied changes to `self`.
@@ -344,106 +858,685 @@ impl LocalChain {
     /// the existing chain and invalidate the block after it (if it exists) by including a block at
     /// the same height but with a different hash to explicitly exclude it as a connection point.
     ///
-    /// Additionally, an empty chain can be updated with any chain, and a chain with a single block
-    /// can have it's block invalidated by an update chain with a block at the same height but
-    /// different hash.
+    /// The genesis block of `self` is never invalidated. An update with a different genesis block
+    /// (or which would otherwise alter the genesis block) is refused.
+    ///
+    /// The update's tip may be lower than the tip of `self`. If the update forks off below the tip
+    /// of `self` (it has a block at the same height as, but with a different hash than, a block of
+    /// `self`), that block and all blocks of `self` above it are invalidated, so the tip of `self`
+    /// becomes lower (refer to [`report_update`] to decide whether to follow such a shorter chain).
+    /// Otherwise, the update is a subset of `self`, and only older blocks are introduced (if
+    /// [`Update::introduce_older_blocks`] is set).
     ///
     /// # Errors
     ///
-    /// An error will occur if the update does not correctly connect with `self`.
+    /// An error will occur if the update does not correctly connect with `self`. An update which
+    /// would alter the genesis block or contradict a trusted checkpoint (refer to
+    /// [`set_trusted_checkpoints`]) cannot connect either, and the error suggests the height of the
+    /// contradicted block.
     ///
     /// Refer to [`Update`] for more about the update struct.
     ///
     /// [module-level documentation]: crate::local_chain
+    /// [`report_update`]: Self::report_update
+    /// [`set_trusted_checkpoints`]: Self::set_trusted_checkpoints
     pub fn apply_update(&mut self, update: Update) -> Result<ChangeSet, CannotConnectError> {
-        let changeset = merge_chains(
-            self.tip.clone(),
-            update.tip.clone(),
-            update.introduce_older_blocks,
-        )?;
-        // `._check_index_is_consistent_with_tip` and `._check_changeset_is_applied` is called in
-        // `.apply_changeset`
-        self.apply_changeset(&changeset)
-            .map_err(|_| CannotConnectError {
-                try_include_height: 0,
-            })?;
+        let changeset = self.update_changeset(&update)?;
+        // `._check_index_is_consistent_with_tip` and `.is_applied` are called in
+        // `.apply_changeset_unchecked`
+        self.apply_changeset_checked(&changeset)
+            .map_err(refused_update)?;
         Ok(changeset)
     }
 
-    /// Update the chain with a given [`Header`] at `height` which you claim is connected to a existing block in the chain.
-    ///
-    /// This is useful when you have a block header that you want to record as part of the chain but
-    /// don't necessarily know that the `prev_blockhash` is in the chain.
+    /// Reports the changes that applying `update` would make, without mutating `self`.
     ///
-    /// This will usually insert two new [`BlockId`]s into the chain: the header's block and the
-    /// header's `prev_blockhash` block. `connected_to` must already be in the chain but is allowed
-    /// to be `prev_blockhash` (in which case only one new block id will be inserted).
-    /// To be successful, `connected_to` must be chosen carefully so that `LocalChain`'s [update
-    /// rules][`apply_update`] are satisfied.
+    /// The [`UpdateReport`] contains the [`ChangeSet`] that [`apply_update`] would return, and
+    /// whether applying the update would reorg the chain (invalidate existing blocks). This can be
+    /// used to decide whether to accept an update which forks off below the tip of `self`.
     ///
     /// # Errors
     ///
-    /// [`ApplyHeaderError::InconsistentBlocks`] occurs if the `connected_to` block and the
-    /// [`Header`] is inconsistent. For example, if the `connected_to` block is the same height as
-    /// `header` or `prev_blockhash`, but has a different block hash. Or if the `connected_to`
-    /// height is greater than the header's `height`.
+    /// An error will occur if the update does not correctly connect with `self`.
     ///
-    /// [`ApplyHeaderError::CannotConnect`] occurs if the internal call to [`apply_update`] fails.
+    /// [`apply_update`]: Self::apply_update
+    pub fn report_update(&self, update: &Update) -> Result<UpdateReport, CannotConnectError> {
+        let changeset = self.update_changeset(update)?;
+        self.check_changeset(&changeset).map_err(refused_update)?;
+        let would_reorg = self.first_invalidated_height(&changeset).is_some();
+        Ok(UpdateReport {
+            changeset,
+            would_reorg,
+        })
+    }
+
+    /// Applies the given `update` to the chain, but only if it purely extends the chain.
+    ///
+    /// This is the same as [`apply_update`], except that an update which would invalidate (remove
+    /// or replace) any existing block is rejected with [`FastForwardError::WouldReorg`]. This
+    /// allows reorgs to require explicit intervention instead of being applied automatically.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the update would reorg the chain, or if the update does not
+    /// correctly connect with `self`. In both cases, `self` is left unchanged.
     ///
     /// [`apply_update`]: Self::apply_update
-    pub fn apply_header_connected_to(
-        &mut self,
-        header: &Header,
-        height: u32,
-        connected_to: BlockId,
-    ) -> Result<ChangeSet, ApplyHeaderError> {
-        let this = BlockId {
-            height,
-            hash: header.block_hash(),
-        };
-        let prev = height.checked_sub(1).map(|prev_height| BlockId {
-            height: prev_height,
-            hash: header.prev_blockhash,
-        });
-        let conn = match connected_to {
-            // `connected_to` can be ignored if same as `this` or `prev` (duplicate)
-            conn if conn == this || Some(conn) == prev => None,
-            // this occurs if:
-            // - `connected_to` height is the same as `prev`, but different hash
-            // - `connected_to` height is the same as `this`, but different hash
-            // - `connected_to` height is greater than `this` (this is not allowed)
-            conn if conn.height >= height.saturating_sub(1) => {
-                return Err(ApplyHeaderError::InconsistentBlocks)
-            }
-            conn => Some(conn),
-        };
+    pub fn fast_forward(&mut self, update: Update) -> Result<ChangeSet, FastForwardError> {
+        let UpdateReport { changeset, .. } = self
+            .report_update(&update)
+            .map_err(FastForwardError::CannotConnect)?;
+        if let Some(height) = self.first_invalidated_height(&changeset) {
+            return Err(FastForwardError::WouldReorg { height });
+        }
+        self.apply_changeset_checked(&changeset)
+            .expect("the changeset is checked by `report_update`");
+        Ok(changeset)
+    }
 
-        let update = Update {
-            tip: CheckPoint::from_block_ids([conn, prev, Some(this)].into_iter().flatten())
-                .expect("block ids must be in order"),
-            introduce_older_blocks: false,
-        };
+    /// Returns the lowest height at which `changeset` removes or replaces an existing block.
+    fn first_invalidated_height(&self, changeset: &ChangeSet) -> Option<u32> {
+        changeset
+            .iter()
+            .find(|(height, hash)| {
+                self.index
+                    .get(height)
+                    .map_or(false, |original_hash| Some(original_hash) != hash.as_ref())
+            })
+            .map(|(&height, _)| height)
+    }
 
-        self.apply_update(update)
-            .map_err(ApplyHeaderError::CannotConnect)
+    /// Returns the [`ChangeSet`] of merging `update` into the chain, without validating it against
+    /// the genesis block and trusted checkpoints.
+    ///
+    /// The chains are not merged if the update cannot change the chain, which makes the common "no
+    /// change" results of a sync essentially free.
+    fn update_changeset(&self, update: &Update) -> Result<ChangeSet, CannotConnectError> {
+        // OPTIMIZATION: the update tip is our tip, or the update is a (stale) part of our chain, so
+        // there is nothing to change
+        if Arc::as_ptr(&self.tip.0) == Arc::as_ptr(&update.tip.0)
+            || self.contains_stale_update(update)
+        {
+            return Ok(ChangeSet::default());
+        }
+        merge_chains(
+            self.tip.clone(),
+            update.tip.clone(),
+            update.introduce_older_blocks,
+        )
     }
 
-    /// Update the chain with a given [`Header`] connecting it with the previous block.
+    /// Returns whether `update` would not change the chain because its tip is already in the chain
+    /// (and, if the update introduces older blocks, all of its other blocks are too).
+    fn contains_stale_update(&self, update: &Update) -> bool {
+        update.tip.height() <= self.tip.height()
+            && self.contains_block(update.tip.block_id())
+            && (!update.introduce_older_blocks
+                || update
+                    .tip
+                    .iter_block_ids()
+                    .all(|block_id| self.contains_block(block_id)))
+    }
+
+    /// Applies the given `update` to the chain, after checking that the update is for the same
+    /// network as `self`.
     ///
-    /// This is a convenience method to call [`apply_header_connected_to`] with the `connected_to`
-    /// parameter being `height-1:prev_blockhash`. If there is no previous block (i.e. genesis), we
-    /// use the current block as `connected_to`.
+    /// This is the same as [`apply_update`], except that an update containing a genesis block
+    /// which differs from that of `self` is rejected with [`ApplyUpdateError::GenesisMismatch`]
+    /// (instead of either invalidating the entire chain or failing with a [`CannotConnectError`]
+    /// that does not hint at the actual problem).
     ///
-    /// [`apply_header_connected_to`]: LocalChain::apply_header_connected_to
-    pub fn apply_header(
-        &mut self,
-        header: &Header,
-        height: u32,
-    ) -> Result<ChangeSet, CannotConnectError> {
-        let connected_to = match height.checked_sub(1) {
-            Some(prev_height) => BlockId {
-                height: prev_height,
-                hash: header.prev_blockhash,
+    /// # Errors
+    ///
+    /// An error will occur if the update's genesis block differs from that of `self`, or if the
+    /// update does not correctly connect with `self`. In both cases, `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_update_checked(&mut self, update: Update) -> Result<ChangeSet, ApplyUpdateError> {
+        let update_lowest = update.tip.genesis();
+        if update_lowest.height() == 0 && update_lowest.hash() != self.genesis_hash() {
+            return Err(ApplyUpdateError::GenesisMismatch {
+                expected: self.genesis_hash(),
+                found: update_lowest.hash(),
+            });
+        }
+        self.apply_update_trusted(update)
+    }
+
+    /// Applies the given `update` to the chain, but only if the chain's tip is `expected_tip`.
+    ///
+    /// This is the same as [`apply_update`], except that [`ApplyUpdateError::TipMoved`] is returned
+    /// if the chain's tip has changed since `expected_tip` was observed. This allows multiple
+    /// workers to update the same chain without overwriting each other's progress.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the chain's tip is not `expected_tip`, or if the update does not
+    /// correctly connect with `self`. In both cases, `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_update_if_tip(
+        &mut self,
+        update: Update,
+        expected_tip: BlockId,
+    ) -> Result<ChangeSet, ApplyUpdateError> {
+        let tip = self.tip.block_id();
+        if tip != expected_tip {
+            return Err(ApplyUpdateError::TipMoved {
+                expected: expected_tip,
+                found: tip,
+            });
+        }
+        self.apply_update_trusted(update)
+    }
+
+    /// Rolls the chain back to `new_tip`, which forks off the chain at or below the current tip.
+    ///
+    /// Applying an update with a lower tip than the current tip is legal: if `new_tip` includes a
+    /// block which conflicts with the chain (same height, different hash), that block and all
+    /// blocks of `self` above it are invalidated. A `new_tip` which does not conflict with the
+    /// chain is already part of it, so nothing is rolled back.
+    ///
+    /// This is the same as [`apply_update`] with `introduce_older_blocks` set to `false`, and is
+    /// intended for when a trusted source reports that the current tip was reorged out.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if `new_tip` does not correctly connect with `self`.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_rollback(&mut self, new_tip: CheckPoint) -> Result<ChangeSet, CannotConnectError> {
+        self.apply_update(new_tip.into_update(false))
+    }
+
+    /// Returns the tip that the chain would have after applying `update`, without mutating `self`.
+    ///
+    /// This uses the same logic as [`apply_update`]. The returned tip can, for example, be used to
+    /// preview the result of a sync before it is applied.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the update does not correctly connect with `self`.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn preview_update(&self, update: &Update) -> Result<CheckPoint, CannotConnectError> {
+        let changeset = self.update_changeset(update)?;
+        self.check_changeset(&changeset).map_err(refused_update)?;
+        apply_changeset_to_checkpoint(self.tip.clone(), &changeset).map_err(|_| {
+            CannotConnectError {
+                try_include_height: 0,
+            }
+        })
+    }
+
+    /// Applies the given `update` to the chain, but only if the update's tip is no higher than
+    /// `max_height`.
+    ///
+    /// This is the same as [`apply_update`], except that an update with a tip above `max_height`
+    /// is rejected with [`ApplyUpdateError::TipTooHigh`]. This guards against untrusted sources
+    /// claiming an implausibly high tip.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the update's tip is above `max_height`, or if the update does not
+    /// correctly connect with `self`. In both cases, `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_update_max_height(
+        &mut self,
+        update: Update,
+        max_height: u32,
+    ) -> Result<ChangeSet, ApplyUpdateError> {
+        let height = update.tip.height();
+        if height > max_height {
+            return Err(ApplyUpdateError::TipTooHigh { height });
+        }
+        self.apply_update_trusted(update)
+    }
+
+    /// Set the trusted checkpoints (height to expected block hash) of the chain.
+    ///
+    /// Every method which mutates the chain rejects (before `self` is mutated) changes that would
+    /// set a trusted height to a different block hash. [`apply_changeset`], [`set_tip`],
+    /// [`replace_subchain`], [`apply_update_checked`], [`apply_update_if_tip`] and
+    /// [`apply_update_max_height`] report this with a [`TrustedCheckpointViolation`]. Other methods
+    /// report it with their own error type: [`apply_update`] (and the methods based on it) fails
+    /// with a [`CannotConnectError`] suggesting the trusted height (as does [`apply_updates`] if
+    /// any single update contradicts a trusted checkpoint), [`insert_block`] and
+    /// [`replace_block`] fail with an [`AlterCheckPointError`], and [`ensure_heights`] skips the
+    /// contradicting block.
+    ///
+    /// This replaces any previously set trusted checkpoints, and the existing blocks of the chain
+    /// are not checked against them.
+    ///
+    /// [`apply_changeset`]: Self::apply_changeset
+    /// [`set_tip`]: Self::set_tip
+    /// [`replace_subchain`]: Self::replace_subchain
+    /// [`apply_update_checked`]: Self::apply_update_checked
+    /// [`apply_update_if_tip`]: Self::apply_update_if_tip
+    /// [`apply_update_max_height`]: Self::apply_update_max_height
+    /// [`apply_update`]: Self::apply_update
+    /// [`apply_updates`]: Self::apply_updates
+    /// [`insert_block`]: Self::insert_block
+    /// [`replace_block`]: Self::replace_block
+    /// [`ensure_heights`]: Self::ensure_heights
+    pub fn set_trusted_checkpoints(&mut self, cps: BTreeMap<u32, BlockHash>) {
+        self.trusted = cps;
+    }
+
+    /// Applies the `update` like [`apply_update`], but rejects the update if it contradicts the
+    /// trusted checkpoints.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    fn apply_update_trusted(&mut self, update: Update) -> Result<ChangeSet, ApplyUpdateError> {
+        let changeset = self
+            .update_changeset(&update)
+            .map_err(ApplyUpdateError::CannotConnect)?;
+        self.apply_changeset_checked(&changeset)
+            .map_err(|err| match err {
+                ApplyChangeSetError::GenesisAltered { expected, found } => {
+                    ApplyUpdateError::GenesisMismatch { expected, found }
+                }
+                ApplyChangeSetError::TrustedCheckpointViolation(err) => {
+                    ApplyUpdateError::TrustedCheckpointViolation(err)
+                }
+                err => ApplyUpdateError::CannotConnect(refused_update(err)),
+            })?;
+        Ok(changeset)
+    }
+
+    /// Applies the given `update` to the chain, ignoring the update's blocks below `birthday`.
+    ///
+    /// The update's checkpoint list is filtered to blocks at or above `birthday` (plus the genesis
+    /// block, if the update contains it, for connectivity) before it is applied like
+    /// [`apply_update`]. This keeps the chain compact when bootstrapping from a wallet birthday,
+    /// even if the source of the update sends the pre-birthday history. Blocks of `self` below
+    /// `birthday` are kept, and `birthday` itself is not stored.
+    ///
+    /// Nothing is applied if no blocks of the update remain after filtering.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the filtered update does not correctly connect with `self` (e.g. if
+    /// the only point of agreement was below `birthday`). In this case, `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_update_from_birthday(
+        &mut self,
+        update: Update,
+        birthday: u32,
+    ) -> Result<ChangeSet, CannotConnectError> {
+        let mut block_ids = update
+            .tip
+            .iter_block_ids()
+            .filter(|block_id| block_id.height == 0 || block_id.height >= birthday)
+            .collect::<Vec<_>>();
+        block_ids.reverse();
+        let tip = match CheckPoint::from_block_ids(block_ids) {
+            Ok(tip) => tip,
+            Err(_) => return Ok(ChangeSet::default()),
+        };
+        self.apply_update(Update {
+            tip,
+            introduce_older_blocks: update.introduce_older_blocks,
+        })
+    }
+
+    /// Applies the given `update` to the chain, returning the resulting changes split into
+    /// `(added, removed)`.
+    ///
+    /// `added` contains the blocks that were introduced (the `Some` entries) and `removed` contains
+    /// the heights that were removed (the `None` entries) of the [`ChangeSet`] that
+    /// [`apply_update`] would return.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the update does not correctly connect with `self`. In this case,
+    /// `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_update_split(
+        &mut self,
+        update: Update,
+    ) -> Result<(ChangeSet, ChangeSet), CannotConnectError> {
+        let changeset = self.apply_update(update)?;
+        Ok(changeset.into_iter().partition(|(_, hash)| hash.is_some()))
+    }
+
+    /// Returns whether applying an update with `update_tip` would introduce older blocks into the
+    /// chain.
+    ///
+    /// This is the case if the update chain contains blocks that are missing from (or differ from)
+    /// `self` below the update's highest point of agreement with `self`. Only then does setting
+    /// [`Update::introduce_older_blocks`] to `true` make a difference when applying the update
+    /// (setting it to `false` allows [`apply_update`] to stop at the point of agreement).
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn would_introduce_older_blocks(&self, update_tip: &CheckPoint) -> bool {
+        let mut agreement_found = false;
+        for cp in update_tip.iter() {
+            if self.contains_block(cp.block_id()) {
+                agreement_found = true;
+            } else if agreement_found {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Applies the given `updates` to the chain in order, returning the combined [`ChangeSet`].
+    ///
+    /// This is the same as calling [`apply_update`] for each update, except that the internal index
+    /// is only rebuilt once after all updates are merged. This is faster when applying many updates
+    /// at once (e.g. when catching up on queued block events).
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if any of the updates do not correctly connect with the chain resulting
+    /// from the updates before it, or if any of the updates is refused by [`apply_update`] (because
+    /// it would alter the genesis block or contradict a trusted checkpoint), even if a later update
+    /// would revert its changes. In this case, `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_updates(
+        &mut self,
+        updates: impl IntoIterator<Item = Update>,
+    ) -> Result<ChangeSet, CannotConnectError> {
+        let mut tip = self.tip.clone();
+        let mut changeset = ChangeSet::default();
+        for update in updates {
+            let update_changeset =
+                merge_chains(tip.clone(), update.tip, update.introduce_older_blocks)?;
+            // each update is checked as if applied on its own, as the genesis block and trusted
+            // checkpoints of the intermediate chains are those of `self`
+            self.check_changeset(&update_changeset)
+                .map_err(refused_update)?;
+            tip = apply_changeset_to_checkpoint(tip, &update_changeset)
+                .expect("merged changesets never remove the genesis block");
+            changeset.extend(update_changeset);
+        }
+
+        // only keep changes relative to the original chain, which are applied at once
+        changeset.retain(|height, hash| self.index.get(height) != hash.as_ref());
+        self.apply_changeset_checked(&changeset)
+            .map_err(refused_update)?;
+        Ok(changeset)
+    }
+
+    /// Returns the block that an update must include to connect with the chain, given the `err`
+    /// returned from a failed attempt to apply the update.
+    ///
+    /// This is the chain's block at [`CannotConnectError::try_include_height`], or `None` if the
+    /// chain has no block at that height. Note that the update may still fail to connect after
+    /// including the suggested block, in which case the new error suggests the next block to
+    /// include.
+    pub fn suggest_connection_block(&self, err: &CannotConnectError) -> Option<BlockId> {
+        let height = err.try_include_height;
+        self.index
+            .get(&height)
+            .map(|&hash| BlockId { height, hash })
+    }
+
+    /// Applies the given `update` to the chain, also returning the tip of the chain from before the
+    /// update was applied.
+    ///
+    /// This is the same as [`apply_update`], but the returned previous tip can be used to restore the
+    /// chain if persisting the returned [`ChangeSet`] fails.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the update does not correctly connect with `self`. In this case,
+    /// `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_update_returning_prev(
+        &mut self,
+        update: Update,
+    ) -> Result<(ChangeSet, CheckPoint), CannotConnectError> {
+        let prev_tip = self.tip();
+        let changeset = self.apply_update(update)?;
+        Ok((changeset, prev_tip))
+    }
+
+    /// Applies the given `update` to the chain, calling `on_event` for each block that is added to
+    /// or removed from the chain.
+    ///
+    /// This is the same as [`apply_update`], except that `on_event` is called with a [`ChainEvent`]
+    /// for each change, in ascending height order. If a block is replaced by another block at the
+    /// same height, the [`ChainEvent::Removed`] of the original block is emitted before the
+    /// [`ChainEvent::Added`] of the replacement.
+    ///
+    /// The events are emitted once the whole update is applied successfully, so `on_event` never
+    /// observes a partially applied update.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the update does not correctly connect with `self` (or is refused, as
+    /// with [`apply_update`]). In this case, `self` is left unchanged and `on_event` is never
+    /// called.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_update_with<F: FnMut(ChainEvent)>(
+        &mut self,
+        update: Update,
+        on_event: F,
+    ) -> Result<ChangeSet, CannotConnectError> {
+        let changeset = self.update_changeset(&update)?;
+        // only look up the original blocks at the changed heights (instead of copying the index)
+        let events = changeset
+            .iter()
+            .flat_map(|(&height, &hash)| {
+                let removed = self
+                    .index
+                    .get(&height)
+                    .map(|&hash| ChainEvent::Removed(BlockId { height, hash }));
+                let added = hash.map(|hash| ChainEvent::Added(BlockId { height, hash }));
+                removed.into_iter().chain(added)
+            })
+            .collect::<Vec<_>>();
+        self.apply_changeset_checked(&changeset)
+            .map_err(refused_update)?;
+        events.into_iter().for_each(on_event);
+        Ok(changeset)
+    }
+
+    /// Applies a stream of `blocks` (in ascending height order) to the chain.
+    ///
+    /// This behaves identically to [`apply_update`] with an [`Update`] constructed from the same
+    /// blocks (with `introduce_older_blocks` set to `true`). However, the update is connected with
+    /// the existing chain as the blocks are consumed, so the caller does not need to hold the
+    /// entire update [`CheckPoint`] list in memory at once. An empty `blocks` iterator results in
+    /// an empty [`ChangeSet`].
+    ///
+    /// # Errors
+    ///
+    /// [`ApplyBlockIdsError::NotAscending`] occurs if `blocks` is not in strictly ascending height
+    /// order.
+    ///
+    /// [`ApplyBlockIdsError::CannotConnect`] occurs if the update does not correctly connect with
+    /// `self`.
+    ///
+    /// In both cases, `self` is left unchanged.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_block_ids(
+        &mut self,
+        blocks: impl Iterator<Item = BlockId>,
+    ) -> Result<ChangeSet, ApplyBlockIdsError> {
+        let mut changeset = ChangeSet::default();
+        // height of the previous update block
+        let mut prev_height: Option<u32> = None;
+        // the highest original block below the lowest update block
+        let mut below_update: Option<u32> = None;
+        // the lowest height where an original block is invalidated, original blocks above this
+        // height that are not in the update are removed
+        let mut invalidated_from: Option<u32> = None;
+        // the highest point of agreement found so far
+        let mut agreement: Option<u32> = None;
+        // whether we know if the original block directly above the point of agreement is
+        // invalidated
+        let mut agreement_settled = true;
+        // whether the update contains blocks above the point of agreement
+        let mut update_above_agreement = false;
+        // the original block that must be included to connect above the point of agreement
+        let mut try_include_height: Option<u32> = None;
+
+        for block in blocks {
+            match prev_height {
+                Some(prev_height) => {
+                    if block.height <= prev_height {
+                        return Err(ApplyBlockIdsError::NotAscending {
+                            height: block.height,
+                        });
+                    }
+                    // original blocks between update blocks are not in the update
+                    for (&height, _) in self
+                        .index
+                        .range((Bound::Excluded(prev_height), Bound::Excluded(block.height)))
+                    {
+                        if invalidated_from.is_some() {
+                            changeset.insert(height, None);
+                        }
+                        if !agreement_settled {
+                            agreement_settled = true;
+                            try_include_height = Some(height);
+                        }
+                    }
+                }
+                None => {
+                    below_update = self
+                        .index
+                        .range(..block.height)
+                        .next_back()
+                        .map(|(&h, _)| h);
+                }
+            }
+            if agreement.is_some() {
+                update_above_agreement = true;
+            }
+
+            match self.index.get(&block.height) {
+                Some(&hash) if hash == block.hash => {
+                    agreement = Some(block.height);
+                    agreement_settled = false;
+                    update_above_agreement = false;
+                    try_include_height = None;
+                }
+                orig => {
+                    if orig.is_some() {
+                        invalidated_from.get_or_insert(block.height);
+                        agreement_settled = true;
+                    }
+                    changeset.insert(block.height, Some(block.hash));
+                }
+            }
+            prev_height = Some(block.height);
+        }
+
+        if let Some(prev_height) = prev_height {
+            for (&height, _) in self
+                .index
+                .range((Bound::Excluded(prev_height), Bound::Unbounded))
+            {
+                if invalidated_from.is_some() {
+                    changeset.insert(height, None);
+                }
+                if !agreement_settled && update_above_agreement {
+                    agreement_settled = true;
+                    try_include_height = Some(height);
+                }
+            }
+        }
+
+        let try_include_height = match agreement {
+            Some(_) => try_include_height,
+            None => below_update,
+        };
+        if let Some(try_include_height) = try_include_height {
+            return Err(ApplyBlockIdsError::CannotConnect(CannotConnectError {
+                try_include_height,
+            }));
+        }
+
+        self.apply_changeset_checked(&changeset)
+            .map_err(|err| ApplyBlockIdsError::CannotConnect(refused_update(err)))?;
+        Ok(changeset)
+    }
+
+    /// Update the chain with a given [`Header`] at `height` which you claim is connected to a existing block in the chain.
+    ///
+    /// This is useful when you have a block header that you want to record as part of the chain but
+    /// don't necessarily know that the `prev_blockhash` is in the chain.
+    ///
+    /// This will usually insert two new [`BlockId`]s into the chain: the header's block and the
+    /// header's `prev_blockhash` block. `connected_to` must already be in the chain but is allowed
+    /// to be `prev_blockhash` (in which case only one new block id will be inserted).
+    /// To be successful, `connected_to` must be chosen carefully so that `LocalChain`'s [update
+    /// rules][`apply_update`] are satisfied.
+    ///
+    /// # Errors
+    ///
+    /// [`ApplyHeaderError::InconsistentBlocks`] occurs if the `connected_to` block and the
+    /// [`Header`] is inconsistent. For example, if the `connected_to` block is the same height as
+    /// `header` or `prev_blockhash`, but has a different block hash. Or if the `connected_to`
+    /// height is greater than the header's `height`.
+    ///
+    /// [`ApplyHeaderError::CannotConnect`] occurs if the internal call to [`apply_update`] fails.
+    ///
+    /// [`apply_update`]: Self::apply_update
+    pub fn apply_header_connected_to(
+        &mut self,
+        header: &Header,
+        height: u32,
+        connected_to: BlockId,
+    ) -> Result<ChangeSet, ApplyHeaderError> {
+        let this = BlockId {
+            height,
+            hash: header.block_hash(),
+        };
+        let prev = height.checked_sub(1).map(|prev_height| BlockId {
+            height: prev_height,
+            hash: header.prev_blockhash,
+        });
+        let conn = match connected_to {
+            // `connected_to` can be ignored if same as `this` or `prev` (duplicate)
+            conn if conn == this || Some(conn) == prev => None,
+            // this occurs if:
+            // - `connected_to` height is the same as `prev`, but different hash
+            // - `connected_to` height is the same as `this`, but different hash
+            // - `connected_to` height is greater than `this` (this is not allowed)
+            conn if conn.height >= height.saturating_sub(1) => {
+                return Err(ApplyHeaderError::InconsistentBlocks)
+            }
+            conn => Some(conn),
+        };
+
+        let update = Update {
+            tip: CheckPoint::from_block_ids([conn, prev, Some(this)].into_iter().flatten())
+                .expect("block ids must be in order"),
+            introduce_older_blocks: false,
+        };
+
+        self.apply_update(update)
+            .map_err(ApplyHeaderError::CannotConnect)
+    }
+
+    /// Update the chain with a given [`Header`] connecting it with the previous block.
+    ///
+    /// This is a convenience method to call [`apply_header_connected_to`] with the `connected_to`
+    /// parameter being `height-1:prev_blockhash`. If there is no previous block (i.e. genesis), we
+    /// use the current block as `connected_to`.
+    ///
+    /// [`apply_header_connected_to`]: LocalChain::apply_header_connected_to
+    pub fn apply_header(
+        &mut self,
+        header: &Header,
+        height: u32,
+    ) -> Result<ChangeSet, CannotConnectError> {
+        let connected_to = match height.checked_sub(1) {
+            Some(prev_height) => BlockId {
+                height: prev_height,
+                hash: header.prev_blockhash,
             },
             None => BlockId {
                 height,
@@ -459,45 +1552,138 @@ impl LocalChain {
             })
     }
 
+    /// Update the chain with a given [`Header`] and report whether a reorg occurred.
+    ///
+    /// This is the same as [`apply_header`], but returns `true` if any existing checkpoint was
+    /// invalidated (either removed or replaced by a block with a different hash), and `false` if
+    /// the header purely extended the chain (or was already in the chain).
+    ///
+    /// [`apply_header`]: LocalChain::apply_header
+    pub fn apply_header_reports_reorg(
+        &mut self,
+        header: &Header,
+        height: u32,
+    ) -> Result<bool, CannotConnectError> {
+        // the update only contains blocks from `height - 1`, so only these can be invalidated
+        let prev_heights = self
+            .index
+            .range(height.saturating_sub(1)..)
+            .map(|(&height, _)| height)
+            .collect::<Vec<_>>();
+        let changeset = self.apply_header(header, height)?;
+        Ok(changeset.keys().any(|height| prev_heights.contains(height)))
+    }
+
+    /// Returns whether the given `changeset` is already applied to the chain.
+    ///
+    /// This is the case if every height in `changeset` has the same value (block hash or absence of
+    /// a block) in the chain. Applying an already-applied `changeset` is a no-op, so this can be
+    /// used to check that applying the same update or changeset again makes no further changes.
+    pub fn is_applied(&self, changeset: &ChangeSet) -> bool {
+        changeset
+            .iter()
+            .all(|(height, exp_hash)| self.index.get(height) == exp_hash.as_ref())
+    }
+
+    /// Returns the inverse of `changeset`, which reverts the changes of `changeset` once applied.
+    ///
+    /// For each height in `changeset`, the inverse records the chain's current block hash at that
+    /// height (or `None` if there is no block at that height). This must be called before
+    /// `changeset` is applied, as the inverse is computed against the chain's current state.
+    pub fn invert_changeset(&self, changeset: &ChangeSet) -> ChangeSet {
+        changeset
+            .keys()
+            .map(|&height| (height, self.index.get(&height).copied()))
+            .collect()
+    }
+
     /// Apply the given `changeset`.
-    pub fn apply_changeset(&mut self, changeset: &ChangeSet) -> Result<(), MissingGenesisError> {
-        if let Some(start_height) = changeset.keys().next().cloned() {
-            // changes after point of agreement
-            let mut extension = BTreeMap::default();
-            // point of agreement
-            let mut base: Option<CheckPoint> = None;
-
-            for cp in self.iter_checkpoints() {
-                if cp.height() >= start_height {
-                    extension.insert(cp.height(), cp.hash());
-                } else {
-                    base = Some(cp);
-                    break;
+    ///
+    /// The `changeset` may remove blocks below other blocks of the chain. [`LocalChain`] is sparse,
+    /// so this leaves a valid chain (the remaining blocks are still linked to each other).
+    ///
+    /// # Errors
+    ///
+    /// [`ApplyChangeSetError::MissingGenesis`] occurs if the resulting chain would not contain a
+    /// genesis block.
+    ///
+    /// [`ApplyChangeSetError::GenesisAltered`] occurs if the `changeset` changes the genesis hash.
+    /// A changeset that touches the genesis height (e.g. the delta of a full reorg) must keep the
+    /// same genesis hash.
+    ///
+    /// [`ApplyChangeSetError::TrustedCheckpointViolation`] occurs if the `changeset` sets a trusted
+    /// height to a different block hash. Refer to [`set_trusted_checkpoints`].
+    ///
+    /// In all cases, `self` is left unchanged.
+    ///
+    /// [`set_trusted_checkpoints`]: Self::set_trusted_checkpoints
+    pub fn apply_changeset(&mut self, changeset: &ChangeSet) -> Result<(), ApplyChangeSetError> {
+        self.apply_changeset_checked(changeset)
+    }
+
+    /// Returns an error if `changeset` alters the genesis hash or sets a trusted height to a
+    /// different block hash.
+    fn check_changeset(&self, changeset: &ChangeSet) -> Result<(), ApplyChangeSetError> {
+        if let Some(&Some(genesis_hash)) = changeset.get(&0) {
+            if genesis_hash != self.genesis_hash() {
+                return Err(ApplyChangeSetError::GenesisAltered {
+                    expected: self.genesis_hash(),
+                    found: genesis_hash,
+                });
+            }
+        }
+        for (&height, &hash) in changeset {
+            if let (Some(&expected), Some(got)) = (self.trusted.get(&height), hash) {
+                if expected != got {
+                    return Err(ApplyChangeSetError::TrustedCheckpointViolation(
+                        TrustedCheckpointViolation {
+                            height,
+                            expected,
+                            got,
+                        },
+                    ));
                 }
             }
+        }
+        Ok(())
+    }
 
-            for (&height, &hash) in changeset {
-                match hash {
-                    Some(hash) => {
-                        extension.insert(height, hash);
-                    }
-                    None => {
-                        extension.remove(&height);
-                    }
-                };
-            }
+    /// Apply the given `changeset` after validating it with [`check_changeset`].
+    ///
+    /// Every method which mutates the chain goes through this (only constructing a chain with
+    /// [`from_changeset`] does not), so that the changesets they return can always be replayed with
+    /// [`apply_changeset`].
+    ///
+    /// [`from_changeset`]: Self::from_changeset
+    ///
+    /// [`check_changeset`]: Self::check_changeset
+    /// [`apply_changeset`]: Self::apply_changeset
+    fn apply_changeset_checked(
+        &mut self,
+        changeset: &ChangeSet,
+    ) -> Result<(), ApplyChangeSetError> {
+        self.check_changeset(changeset)?;
+        self.apply_changeset_unchecked(changeset)
+            .map_err(ApplyChangeSetError::MissingGenesis)
+    }
 
-            let new_tip = match base {
-                Some(base) => base
-                    .extend(extension.into_iter().map(BlockId::from))
-                    .expect("extension is strictly greater than base"),
-                None => LocalChain::from_blocks(extension)?.tip(),
-            };
-            self.tip = new_tip;
+    /// Apply the given `changeset` without validating it against the chain.
+    ///
+    /// Only the checkpoints and index entries at or above the lowest height of the `changeset` are
+    /// touched, so appending a block above the tip is `O(log n)` in the chain depth (for updating
+    /// the index) rather than `O(n)`. Note that the consistency checks of debug builds are `O(n)`.
+    fn apply_changeset_unchecked(
+        &mut self,
+        changeset: &ChangeSet,
+    ) -> Result<(), MissingGenesisError> {
+        if let Some(start_height) = changeset.keys().next().cloned() {
+            self.tip = apply_changeset_to_checkpoint(self.tip.clone(), changeset)?;
+            let prev_index = self.index.split_off(&start_height);
             self.reindex(start_height);
+            self.record_changes(&prev_index, changeset);
 
             debug_assert!(self._check_index_is_consistent_with_tip());
-            debug_assert!(self._check_changeset_is_applied(changeset));
+            debug_assert!(self.is_applied(changeset));
         }
 
         Ok(())
@@ -507,7 +1693,8 @@ impl LocalChain {
     ///
     /// # Errors
     ///
-    /// Replacing the block hash of an existing checkpoint will result in an error.
+    /// Replacing the block hash of an existing checkpoint (or inserting a block which contradicts a
+    /// trusted checkpoint) will result in an error.
     pub fn insert_block(&mut self, block_id: BlockId) -> Result<ChangeSet, AlterCheckPointError> {
         if let Some(&original_hash) = self.index.get(&block_id.height) {
             if original_hash != block_id.hash {
@@ -523,15 +1710,52 @@ impl LocalChain {
 
         let mut changeset = ChangeSet::default();
         changeset.insert(block_id.height, Some(block_id.hash));
-        self.apply_changeset(&changeset)
-            .map_err(|_| AlterCheckPointError {
-                height: 0,
-                original_hash: self.genesis_hash(),
-                update_hash: changeset.get(&0).cloned().flatten(),
-            })?;
+        self.apply_changeset_checked(&changeset)
+            .map_err(refused_block)?;
         Ok(changeset)
     }
 
+    /// Inserts the anchor blocks of `anchors` which are missing from the chain.
+    ///
+    /// For each [`Anchor::anchor_block`] height that is not in the chain, `resolve` is called to
+    /// get the block hash at that height, which is then inserted. Heights that `resolve` returns
+    /// `None` for are skipped. This ensures that the confirmation blocks of anchored transactions
+    /// are represented in the chain, so that they can be queried with [`ChainOracle`] methods.
+    ///
+    /// The resolved hashes are trusted in the same way as blocks inserted with [`insert_block`],
+    /// except that a resolved hash which contradicts a trusted checkpoint (refer to
+    /// [`set_trusted_checkpoints`]) is skipped. Only missing heights are inserted, so this never
+    /// alters existing blocks and cannot fail.
+    ///
+    /// [`insert_block`]: Self::insert_block
+    /// [`set_trusted_checkpoints`]: Self::set_trusted_checkpoints
+    pub fn ensure_heights<A: Anchor>(
+        &mut self,
+        anchors: impl IntoIterator<Item = A>,
+        resolve: impl Fn(u32) -> Option<BlockHash>,
+    ) -> ChangeSet {
+        let mut changeset = ChangeSet::default();
+        for anchor in anchors {
+            let height = anchor.anchor_block().height;
+            if self.index.contains_key(&height) || changeset.contains_key(&height) {
+                continue;
+            }
+            if let Some(hash) = resolve(height) {
+                // a hash which contradicts a trusted checkpoint is skipped
+                if self
+                    .trusted
+                    .get(&height)
+                    .map_or(true, |&trusted| trusted == hash)
+                {
+                    changeset.insert(height, Some(hash));
+                }
+            }
+        }
+        self.apply_changeset_checked(&changeset)
+            .expect("only missing heights which agree with the trusted checkpoints are inserted");
+        changeset
+    }
+
     /// Removes blocks from (and inclusive of) the given `block_id`.
     ///
     /// This will remove blocks with a height equal or greater than `block_id`, but only if
@@ -546,12 +1770,208 @@ impl LocalChain {
             return Ok(ChangeSet::default());
         }
 
-        let changeset = self
-            .index
-            .range(block_id.height..)
-            .map(|(&height, _)| (height, None))
-            .collect::<ChangeSet>();
-        self.apply_changeset(&changeset).map(|_| changeset)
+        let changeset = self
+            .index
+            .range(block_id.height..)
+            .map(|(&height, _)| (height, None))
+            .collect::<ChangeSet>();
+        self.apply_changeset_checked(&changeset)
+            .map(|_| changeset)
+            .map_err(|err| match err {
+                ApplyChangeSetError::MissingGenesis(err) => err,
+                err => unreachable!("removing blocks is never refused: {}", err),
+            })
+    }
+
+    /// Sets the block at `block_id.height` to `block_id` and removes all blocks above it.
+    ///
+    /// This is a single-block reorg. Unlike [`insert_block`], the block hash of an existing
+    /// checkpoint can be replaced. The returned [`ChangeSet`] contains both the replaced (or
+    /// inserted) block and the removed blocks.
+    ///
+    /// # Errors
+    ///
+    /// The genesis block cannot be replaced, so an [`AlterCheckPointError`] occurs if `block_id` is
+    /// at height 0 but has a different hash than the chain's genesis block. The same error occurs if
+    /// `block_id` contradicts a trusted checkpoint. In both cases, `self` is left unchanged.
+    ///
+    /// [`insert_block`]: Self::insert_block
+    pub fn replace_block(&mut self, block_id: BlockId) -> Result<ChangeSet, AlterCheckPointError> {
+        let mut changeset = self
+            .index
+            .range((Bound::Excluded(block_id.height), Bound::Unbounded))
+            .map(|(&height, _)| (height, None))
+            .collect::<ChangeSet>();
+        if !self.contains_block(block_id) {
+            changeset.insert(block_id.height, Some(block_id.hash));
+        }
+        self.apply_changeset_checked(&changeset)
+            .map_err(refused_block)?;
+        Ok(changeset)
+    }
+
+    /// Returns the blocks that [`disconnect_from`] would remove for the given `block_id`, without
+    /// mutating the chain.
+    ///
+    /// The returned blocks are in ascending height order and start with `block_id`. An empty vec is
+    /// returned if `block_id` does not exist in the chain.
+    ///
+    /// [`disconnect_from`]: Self::disconnect_from
+    pub fn descendants_invalidated_by(&self, block_id: BlockId) -> Vec<BlockId> {
+        if !self.contains_block(block_id) {
+            return Vec::new();
+        }
+        self.index
+            .range(block_id.height..)
+            .map(BlockId::from)
+            .collect()
+    }
+
+    /// Replaces all blocks above `fork_point` with `new_blocks`.
+    ///
+    /// `new_blocks` must be in strictly ascending height order and must all be above `fork_point`.
+    /// The removal of the original blocks and the insertion of `new_blocks` is applied as a single
+    /// [`ChangeSet`], which is returned.
+    ///
+    /// # Errors
+    ///
+    /// [`ReplaceError::MissingForkPoint`] occurs if `fork_point` is not in the chain.
+    ///
+    /// [`ReplaceError::InvalidOrder`] occurs if `new_blocks` are not in strictly ascending height
+    /// order, or are not above `fork_point`.
+    ///
+    /// [`ReplaceError::TrustedCheckpointViolation`] occurs if `new_blocks` contradict a trusted
+    /// checkpoint. Refer to [`set_trusted_checkpoints`].
+    ///
+    /// In all cases, `self` is left unchanged.
+    ///
+    /// [`set_trusted_checkpoints`]: Self::set_trusted_checkpoints
+    pub fn replace_subchain(
+        &mut self,
+        fork_point: BlockId,
+        new_blocks: impl IntoIterator<Item = BlockId>,
+    ) -> Result<ChangeSet, ReplaceError> {
+        if !self.contains_block(fork_point) {
+            return Err(ReplaceError::MissingForkPoint(fork_point));
+        }
+
+        let mut changeset = self
+            .index
+            .range((Bound::Excluded(fork_point.height), Bound::Unbounded))
+            .map(|(&height, _)| (height, None))
+            .collect::<ChangeSet>();
+        let mut prev_height = fork_point.height;
+        for block in new_blocks {
+            if block.height <= prev_height {
+                return Err(ReplaceError::InvalidOrder {
+                    height: block.height,
+                });
+            }
+            changeset.insert(block.height, Some(block.hash));
+            prev_height = block.height;
+        }
+
+        self.apply_changeset_checked(&changeset)
+            .map_err(|err| match err {
+                ApplyChangeSetError::TrustedCheckpointViolation(err) => {
+                    ReplaceError::TrustedCheckpointViolation(err)
+                }
+                err => unreachable!("fork point is kept so genesis is never altered: {}", err),
+            })?;
+        Ok(changeset)
+    }
+
+    /// Removes all checkpoints except for the genesis checkpoint and the `n` most recent
+    /// checkpoints.
+    ///
+    /// The returned [`ChangeSet`] contains the removed heights. After shrinking, the chain no longer
+    /// knows about the removed blocks, so [`is_block_in_chain`] will return `None` (unknown) for
+    /// blocks at those heights and [`contains_block`] will return `false`.
+    ///
+    /// [`is_block_in_chain`]: ChainOracle::is_block_in_chain
+    /// [`contains_block`]: Self::contains_block
+    pub fn shrink_to_recent(&mut self, n: u32) -> ChangeSet {
+        let changeset = self
+            .index
+            .keys()
+            .rev()
+            .skip(n as usize)
+            .filter(|&&height| height != 0)
+            .map(|&height| (height, None))
+            .collect::<ChangeSet>();
+        self.apply_changeset_checked(&changeset)
+            .expect("genesis is never removed");
+        changeset
+    }
+
+    /// Get the cumulative counters of changes made to the chain since it was constructed.
+    pub fn stats(&self) -> ChainStats {
+        self.stats
+    }
+
+    /// Start accumulating the changes made to the chain into an internal staged [`ChangeSet`].
+    ///
+    /// Once enabled, every change applied to the chain is also appended to the staged changeset,
+    /// which can be retrieved (and cleared) with [`take_staged`]. The changesets returned by the
+    /// individual methods are unaffected. Calling this when staging is already enabled does
+    /// nothing.
+    ///
+    /// [`take_staged`]: Self::take_staged
+    pub fn enable_staging(&mut self) {
+        if self.staged.is_none() {
+            self.staged = Some(ChangeSet::default());
+        }
+    }
+
+    /// Returns the changes staged since staging was enabled or `take_staged` was last called, and
+    /// clears them.
+    ///
+    /// This is empty if staging was never enabled with [`enable_staging`].
+    ///
+    /// [`enable_staging`]: Self::enable_staging
+    pub fn take_staged(&mut self) -> ChangeSet {
+        self.staged
+            .as_mut()
+            .map(core::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Records the applied `changeset` in the stats (and the staged changes, if enabled), where
+    /// `prev_index` contains (at least) the entries of the index at the changeset's heights from
+    /// before the `changeset` was applied.
+    fn record_changes(&mut self, prev_index: &BTreeMap<u32, BlockHash>, changeset: &ChangeSet) {
+        if let Some(staged) = &mut self.staged {
+            Append::append(staged, changeset.clone());
+        }
+        let mut invalidated = 0;
+        for (height, hash) in changeset {
+            let prev_hash = prev_index.get(height);
+            if prev_hash == hash.as_ref() {
+                continue;
+            }
+            if hash.is_some() {
+                self.stats.blocks_added += 1;
+            }
+            if prev_hash.is_some() {
+                invalidated += 1;
+            }
+        }
+        self.stats.blocks_removed += invalidated;
+        if invalidated > 0 {
+            self.stats.reorgs += 1;
+        }
+    }
+
+    /// Rebuilds the entire internal index from the current tip's checkpoint list.
+    ///
+    /// The index is kept consistent with the tip by all methods of [`LocalChain`], so this is
+    /// normally unnecessary. It is a repair hatch to guarantee that the index matches the tip
+    /// (which can be confirmed with [`integrity_report`]). This is `O(n)` in the number of
+    /// checkpoints.
+    ///
+    /// [`integrity_report`]: Self::integrity_report
+    pub fn reindex_from_tip(&mut self) {
+        self.reindex(0);
     }
 
     /// Reindex the heights in the chain from (and including) `from` height
@@ -571,6 +1991,103 @@ impl LocalChain {
         self.index.iter().map(|(k, v)| (*k, Some(*v))).collect()
     }
 
+    /// Consumes the chain and derives an initial [`ChangeSet`] from it.
+    ///
+    /// This is the same as [`initial_changeset`], but avoids cloning the internal index.
+    ///
+    /// [`initial_changeset`]: Self::initial_changeset
+    pub fn into_changeset(self) -> ChangeSet {
+        self.index.into_iter().map(|(k, v)| (k, Some(v))).collect()
+    }
+
+    /// Computes a fingerprint of the chain's blocks.
+    ///
+    /// This is a SHA256 digest over the blocks of the chain in ascending height order. Each block
+    /// is committed to as its height (4 bytes, big-endian) followed by its 32-byte block hash.
+    /// Chains that are equal (as per [`PartialEq`]) will always have the same fingerprint, while any
+    /// difference in the blocks changes the fingerprint. This allows two parties to cheaply check
+    /// whether their chains are in sync before exchanging a full [`ChangeSet`].
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut engine = sha256::Hash::engine();
+        for (height, hash) in &self.index {
+            engine.input(&height.to_be_bytes());
+            engine.input(hash.as_byte_array());
+        }
+        sha256::Hash::from_engine(engine).to_byte_array()
+    }
+
+    /// Encodes the chain into a compact binary representation.
+    ///
+    /// The blocks are encoded in ascending height order. Each block is encoded as its height (an
+    /// unsigned LEB128 varint) followed by its 32-byte block hash. Use [`decode`] to recover the
+    /// chain.
+    ///
+    /// [`decode`]: Self::decode
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.index.len() * (32 + 5));
+        for (&height, hash) in &self.index {
+            let mut height = height;
+            loop {
+                let byte = (height & 0x7f) as u8;
+                height >>= 7;
+                if height == 0 {
+                    bytes.push(byte);
+                    break;
+                }
+                bytes.push(byte | 0x80);
+            }
+            bytes.extend_from_slice(hash.as_byte_array());
+        }
+        bytes
+    }
+
+    /// Decodes a chain from the binary representation produced by [`encode`].
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if `bytes` is truncated, if a height is not minimally encoded, if the
+    /// blocks are not in strictly ascending height order, or if the chain does not contain a
+    /// genesis block.
+    ///
+    /// [`encode`]: Self::encode
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut blocks = BTreeMap::new();
+        let mut prev_height: Option<u32> = None;
+        let mut bytes = bytes.iter();
+        while bytes.len() > 0 {
+            let mut height = 0_u32;
+            let mut shift = 0_u32;
+            loop {
+                let byte = *bytes.next().ok_or(DecodeError::Truncated)?;
+                if shift > 28 || (shift == 28 && byte & 0x7f > 0x0f) {
+                    return Err(DecodeError::InvalidHeight);
+                }
+                height |= ((byte & 0x7f) as u32) << shift;
+                if byte & 0x80 == 0 {
+                    // a trailing zero byte would have been omitted by `encode`
+                    if byte == 0 && shift > 0 {
+                        return Err(DecodeError::NonCanonicalHeight);
+                    }
+                    break;
+                }
+                shift += 7;
+            }
+            if prev_height.map_or(false, |prev_height| height <= prev_height) {
+                return Err(DecodeError::NotAscending { height });
+            }
+            if bytes.len() < 32 {
+                return Err(DecodeError::Truncated);
+            }
+            let mut hash = [0_u8; 32];
+            for b in hash.iter_mut() {
+                *b = *bytes.next().expect("length is checked");
+            }
+            blocks.insert(height, BlockHash::from_byte_array(hash));
+            prev_height = Some(height);
+        }
+        Self::from_blocks(blocks).map_err(DecodeError::MissingGenesis)
+    }
+
     /// Iterate over checkpoints in descending height order.
     pub fn iter_checkpoints(&self) -> CheckPointIter {
         CheckPointIter {
@@ -578,11 +2095,147 @@ impl LocalChain {
         }
     }
 
+    /// Iterate over the blocks of the chain in ascending height order.
+    ///
+    /// Unlike [`iter_checkpoints`], this is backed by the internal index so it can also be
+    /// iterated in descending height order (with [`Iterator::rev`]) or consumed from both ends.
+    ///
+    /// [`iter_checkpoints`]: Self::iter_checkpoints
+    pub fn iter_blocks(&self) -> impl DoubleEndedIterator<Item = BlockId> + '_ {
+        self.index.iter().map(BlockId::from)
+    }
+
+    /// Iterate over the blocks of the chain at or above the `start` height, in ascending height
+    /// order.
+    ///
+    /// Only blocks that exist in the chain are yielded, so heights without a checkpoint are
+    /// skipped for sparse chains.
+    pub fn block_ids_from(&self, start: u32) -> impl Iterator<Item = BlockId> + '_ {
+        self.index.range(start..).map(BlockId::from)
+    }
+
     /// Get a reference to the internal index mapping the height to block hash.
     pub fn blocks(&self) -> &BTreeMap<u32, BlockHash> {
         &self.index
     }
 
+    /// Estimates the number of heap bytes occupied by the chain.
+    ///
+    /// This covers both the checkpoint linked list (refer to [`CheckPoint::estimated_heap_bytes`])
+    /// and the entries of the internal index, and is not exact.
+    pub fn estimated_heap_bytes(&self) -> usize {
+        let index_bytes = self.index.len() * core::mem::size_of::<(u32, BlockHash)>();
+        self.tip.estimated_heap_bytes() + index_bytes
+    }
+
+    /// Get the [`BlockId`] that is `depth` blocks below the tip.
+    ///
+    /// Returns `None` if `depth` is greater than the tip height, or if the chain does not have a
+    /// checkpoint at height `tip_height - depth`. `tip_ancestor_at(0)` returns the tip.
+    pub fn tip_ancestor_at(&self, depth: u32) -> Option<BlockId> {
+        let height = self.tip.height().checked_sub(depth)?;
+        self.index
+            .get(&height)
+            .map(|&hash| BlockId { height, hash })
+    }
+
+    /// Get the [`BlockId`] of the highest checkpoint at or below the given `height`.
+    ///
+    /// This looks up the internal index and does not require constructing a [`CheckPoint`]. The
+    /// genesis block is returned if there are no other checkpoints at or below `height`.
+    pub fn block_at_or_below(&self, height: u32) -> BlockId {
+        self.index
+            .range(..=height)
+            .next_back()
+            .map(BlockId::from)
+            .unwrap_or_else(|| BlockId {
+                height: 0,
+                hash: self.genesis_hash(),
+            })
+    }
+
+    /// Returns the range of heights, ending at the tip, over which the chain has a checkpoint at
+    /// every height.
+    ///
+    /// If the chain is fully contiguous, this is `0..=tip_height`. Within this range, the chain has
+    /// complete knowledge of the best chain, whereas below it (where blocks may be missing) queries
+    /// such as [`is_block_in_chain`] may return `None`.
+    ///
+    /// [`is_block_in_chain`]: ChainOracle::is_block_in_chain
+    pub fn contiguous_suffix(&self) -> RangeInclusive<u32> {
+        let tip_height = self.tip.height();
+        let mut start = tip_height;
+        for &height in self.index.keys().rev().skip(1) {
+            if start.checked_sub(1) != Some(height) {
+                break;
+            }
+            start = height;
+        }
+        start..=tip_height
+    }
+
+    /// Returns the heights in `start..=target_tip` (in ascending order) that do not have a
+    /// checkpoint in the chain.
+    ///
+    /// This is useful for planning which block headers to fetch when catching up to `target_tip`.
+    /// An empty vec is returned if `start > target_tip`.
+    pub fn heights_to_fetch(&self, start: u32, target_tip: u32) -> Vec<u32> {
+        (start..=target_tip)
+            .filter(|height| !self.index.contains_key(height))
+            .collect()
+    }
+
+    /// Returns whether the chain contains a checkpoint at the given `height`.
+    pub fn height_exists(&self, height: u32) -> bool {
+        self.index.contains_key(&height)
+    }
+
+    /// Returns whether the chain contains a checkpoint with the given block `hash`.
+    ///
+    /// There is no index by hash, so this is a linear scan over all checkpoints (`O(n)`). Prefer
+    /// [`contains_block`] if the height is known.
+    ///
+    /// [`contains_block`]: Self::contains_block
+    pub fn hash_exists(&self, hash: BlockHash) -> bool {
+        self.index.values().any(|&h| h == hash)
+    }
+
+    /// Returns whether the chain contains a checkpoint of the given `block_id`.
+    pub fn contains_block(&self, block_id: BlockId) -> bool {
+        self.index.get(&block_id.height) == Some(&block_id.hash)
+    }
+
+    /// Returns whether the chain contains any of the given `blocks`.
+    ///
+    /// This stops at the first block that is found in the chain.
+    pub fn contains_any(&self, blocks: impl IntoIterator<Item = BlockId>) -> bool {
+        blocks
+            .into_iter()
+            .any(|block_id| self.contains_block(block_id))
+    }
+
+    /// Returns whether the chain contains all of the given `blocks`.
+    ///
+    /// This stops at the first block that is not found in the chain.
+    pub fn contains_all(&self, blocks: impl IntoIterator<Item = BlockId>) -> bool {
+        blocks
+            .into_iter()
+            .all(|block_id| self.contains_block(block_id))
+    }
+
+    /// Checks the integrity of the chain's internal state and returns an [`IntegrityReport`].
+    ///
+    /// Unlike the internal consistency checks (which are only run as debug assertions), this can be
+    /// used in release builds, for example to verify that a chain loaded from persisted state is
+    /// not corrupted.
+    pub fn integrity_report(&self) -> IntegrityReport {
+        IntegrityReport {
+            index_matches_tip: self._check_index_is_consistent_with_tip(),
+            genesis_present: self.index.contains_key(&0) && self.tip.genesis().height() == 0,
+            contiguous: *self.contiguous_suffix().start() == 0,
+        }
+    }
+
     fn _check_index_is_consistent_with_tip(&self) -> bool {
         let tip_history = self
             .tip
@@ -591,15 +2244,6 @@ impl LocalChain {
             .collect::<BTreeMap<_, _>>();
         self.index == tip_history
     }
-
-    fn _check_changeset_is_applied(&self, changeset: &ChangeSet) -> bool {
-        for (height, exp_hash) in changeset {
-            if self.index.get(height) != exp_hash.as_ref() {
-                return false;
-            }
-        }
-        true
-    }
 }
 
 /// An error which occurs when a [`LocalChain`] is constructed without a genesis checkpoint.
@@ -618,6 +2262,39 @@ impl core::fmt::Display for MissingGenesisError {
 #[cfg(feature = "std")]
 impl std::error::Error for MissingGenesisError {}
 
+/// The error type for [`LocalChain::apply_changeset`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApplyChangeSetError {
+    /// Occurs when the resulting chain would not contain a genesis block.
+    MissingGenesis(MissingGenesisError),
+    /// Occurs when the changeset changes the genesis hash.
+    GenesisAltered {
+        /// The genesis hash of the chain.
+        expected: BlockHash,
+        /// The genesis hash of the changeset.
+        found: BlockHash,
+    },
+    /// Occurs when the changeset contradicts a trusted checkpoint.
+    TrustedCheckpointViolation(TrustedCheckpointViolation),
+}
+
+impl core::fmt::Display for ApplyChangeSetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ApplyChangeSetError::MissingGenesis(err) => core::fmt::Display::fmt(err, f),
+            ApplyChangeSetError::GenesisAltered { expected, found } => write!(
+                f,
+                "changeset alters the genesis hash from {} to {}",
+                expected, found
+            ),
+            ApplyChangeSetError::TrustedCheckpointViolation(err) => core::fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ApplyChangeSetError {}
+
 /// Represents a failure when trying to insert/remove a checkpoint to/from [`LocalChain`].
 #[derive(Clone, Debug, PartialEq)]
 pub struct AlterCheckPointError {
@@ -693,6 +2370,348 @@ impl core::fmt::Display for ApplyHeaderError {
 #[cfg(feature = "std")]
 impl std::error::Error for ApplyHeaderError {}
 
+/// The error type for [`LocalChain::apply_block_ids`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyBlockIdsError {
+    /// Occurs when the update cannot connect with the original chain.
+    CannotConnect(CannotConnectError),
+    /// Occurs when the blocks are not in strictly ascending height order.
+    NotAscending {
+        /// The height of the first out-of-order block.
+        height: u32,
+    },
+}
+
+impl core::fmt::Display for ApplyBlockIdsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ApplyBlockIdsError::CannotConnect(err) => core::fmt::Display::fmt(err, f),
+            ApplyBlockIdsError::NotAscending { height } => write!(
+                f,
+                "the block at height {} is not in ascending height order",
+                height
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ApplyBlockIdsError {}
+
+/// The error type for [`LocalChain::apply_update_checked`], [`LocalChain::apply_update_if_tip`]
+/// and [`LocalChain::apply_update_max_height`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyUpdateError {
+    /// Occurs when the update cannot connect with the original chain.
+    CannotConnect(CannotConnectError),
+    /// Occurs when the update's genesis block differs from the original chain's genesis block.
+    GenesisMismatch {
+        /// The genesis hash of the original chain.
+        expected: BlockHash,
+        /// The genesis hash of the update.
+        found: BlockHash,
+    },
+    /// Occurs when the original chain's tip is not the expected tip.
+    TipMoved {
+        /// The expected tip of the original chain.
+        expected: BlockId,
+        /// The actual tip of the original chain.
+        found: BlockId,
+    },
+    /// Occurs when the update's tip is above the maximum accepted height.
+    TipTooHigh {
+        /// The height of the update's tip.
+        height: u32,
+    },
+    /// Occurs when the update contradicts a trusted checkpoint.
+    TrustedCheckpointViolation(TrustedCheckpointViolation),
+}
+
+impl core::fmt::Display for ApplyUpdateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ApplyUpdateError::CannotConnect(err) => core::fmt::Display::fmt(err, f),
+            ApplyUpdateError::GenesisMismatch { expected, found } => write!(
+                f,
+                "the update's genesis hash {} does not match the chain's genesis hash {}",
+                found, expected
+            ),
+            ApplyUpdateError::TipMoved { expected, found } => write!(
+                f,
+                "the chain's tip {:?} is not the expected tip {:?}",
+                found, expected
+            ),
+            ApplyUpdateError::TipTooHigh { height } => write!(
+                f,
+                "the update's tip height {} is above the maximum accepted height",
+                height
+            ),
+            ApplyUpdateError::TrustedCheckpointViolation(err) => core::fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ApplyUpdateError {}
+
+/// Occurs when a change would set a trusted checkpoint's height to a different block hash.
+///
+/// Refer to [`LocalChain::set_trusted_checkpoints`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrustedCheckpointViolation {
+    /// The height of the trusted checkpoint.
+    pub height: u32,
+    /// The block hash of the trusted checkpoint.
+    pub expected: BlockHash,
+    /// The block hash that the change attempted to set.
+    pub got: BlockHash,
+}
+
+impl core::fmt::Display for TrustedCheckpointViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "block hash {} at height {} contradicts the trusted checkpoint {}",
+            self.got, self.height, self.expected
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TrustedCheckpointViolation {}
+
+/// The error type for [`LocalChain::fast_forward`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FastForwardError {
+    /// Occurs when the update cannot connect with the original chain.
+    CannotConnect(CannotConnectError),
+    /// Occurs when the update would remove or replace existing blocks of the original chain.
+    WouldReorg {
+        /// The lowest height of an existing block that would be removed or replaced.
+        height: u32,
+    },
+}
+
+impl core::fmt::Display for FastForwardError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FastForwardError::CannotConnect(err) => core::fmt::Display::fmt(err, f),
+            FastForwardError::WouldReorg { height } => {
+                write!(f, "the update would reorg the chain from height {}", height)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FastForwardError {}
+
+/// The error type for [`LocalChain::decode`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// Occurs when the bytes end in the middle of a block.
+    Truncated,
+    /// Occurs when an encoded height does not fit in a `u32`.
+    InvalidHeight,
+    /// Occurs when an encoded height is not minimally encoded.
+    NonCanonicalHeight,
+    /// Occurs when the blocks are not in strictly ascending height order.
+    NotAscending {
+        /// The height of the first out-of-order block.
+        height: u32,
+    },
+    /// Occurs when the decoded chain does not contain a genesis block.
+    MissingGenesis(MissingGenesisError),
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::Truncated => write!(f, "the encoded chain is truncated"),
+            DecodeError::InvalidHeight => write!(f, "an encoded height does not fit in a u32"),
+            DecodeError::NonCanonicalHeight => {
+                write!(f, "an encoded height is not minimally encoded")
+            }
+            DecodeError::NotAscending { height } => write!(
+                f,
+                "the block at height {} is not in ascending height order",
+                height
+            ),
+            DecodeError::MissingGenesis(err) => core::fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// The error type for [`LocalChain::from_blocks_sorted`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromBlocksSortedError {
+    /// Occurs when the blocks are not in strictly ascending height order.
+    NotAscending {
+        /// The height of the first out-of-order block.
+        height: u32,
+    },
+    /// Occurs when the blocks do not contain a genesis block.
+    MissingGenesis(MissingGenesisError),
+}
+
+impl core::fmt::Display for FromBlocksSortedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FromBlocksSortedError::NotAscending { height } => write!(
+                f,
+                "the block at height {} is not in ascending height order",
+                height
+            ),
+            FromBlocksSortedError::MissingGenesis(err) => core::fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromBlocksSortedError {}
+
+/// The error type for [`LocalChain::replace_subchain`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplaceError {
+    /// Occurs when the fork point is not in the chain.
+    MissingForkPoint(BlockId),
+    /// Occurs when the new blocks are not in strictly ascending height order above the fork point.
+    InvalidOrder {
+        /// The height of the first out-of-order block.
+        height: u32,
+    },
+    /// Occurs when the new blocks contradict a trusted checkpoint.
+    TrustedCheckpointViolation(TrustedCheckpointViolation),
+}
+
+impl core::fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReplaceError::MissingForkPoint(fork_point) => {
+                write!(f, "the fork point {:?} is not in the chain", fork_point)
+            }
+            ReplaceError::InvalidOrder { height } => write!(
+                f,
+                "the block at height {} is not in ascending order above the fork point",
+                height
+            ),
+            ReplaceError::TrustedCheckpointViolation(err) => core::fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplaceError {}
+
+/// Converts the error of applying the changeset of an update into a [`CannotConnectError`].
+///
+/// The update must agree with the chain's block at the height which the changeset contradicts, so
+/// that height is suggested for inclusion.
+fn refused_update(err: ApplyChangeSetError) -> CannotConnectError {
+    let try_include_height = match err {
+        ApplyChangeSetError::TrustedCheckpointViolation(err) => err.height,
+        ApplyChangeSetError::MissingGenesis(_) | ApplyChangeSetError::GenesisAltered { .. } => 0,
+    };
+    CannotConnectError { try_include_height }
+}
+
+/// Converts the error of applying the changeset of a block insertion or replacement into an
+/// [`AlterCheckPointError`].
+///
+/// The block may not replace the genesis block or contradict a trusted checkpoint, so the genesis
+/// hash or the trusted hash is reported as the original hash.
+fn refused_block(err: ApplyChangeSetError) -> AlterCheckPointError {
+    match err {
+        ApplyChangeSetError::GenesisAltered { expected, found } => AlterCheckPointError {
+            height: 0,
+            original_hash: expected,
+            update_hash: Some(found),
+        },
+        ApplyChangeSetError::TrustedCheckpointViolation(err) => AlterCheckPointError {
+            height: err.height,
+            original_hash: err.expected,
+            update_hash: Some(err.got),
+        },
+        ApplyChangeSetError::MissingGenesis(err) => {
+            unreachable!(
+                "inserting or replacing a block keeps the genesis block: {}",
+                err
+            )
+        }
+    }
+}
+
+/// Applies the `changeset` to the checkpoint list of `tip`, returning the new tip.
+///
+/// The checkpoints below the lowest height of the `changeset` are shared with `tip` (and are not
+/// walked), so this is `O(1)` when appending blocks above `tip`.
+fn apply_changeset_to_checkpoint(
+    tip: CheckPoint,
+    changeset: &ChangeSet,
+) -> Result<CheckPoint, MissingGenesisError> {
+    let start_height = match changeset.keys().next() {
+        Some(&height) => height,
+        None => return Ok(tip),
+    };
+
+    // changes after point of agreement
+    let mut extension = BTreeMap::default();
+    // point of agreement
+    let mut base: Option<CheckPoint> = None;
+
+    for cp in tip.iter() {
+        if cp.height() >= start_height {
+            extension.insert(cp.height(), cp.hash());
+        } else {
+            base = Some(cp);
+            break;
+        }
+    }
+
+    for (&height, &hash) in changeset {
+        match hash {
+            Some(hash) => {
+                extension.insert(height, hash);
+            }
+            None => {
+                extension.remove(&height);
+            }
+        };
+    }
+
+    Ok(match base {
+        Some(base) => base
+            .extend(extension.into_iter().map(BlockId::from))
+            .expect("extension is strictly greater than base"),
+        None => LocalChain::from_blocks(extension)?.tip(),
+    })
+}
+
+/// Computes the [`ChangeSet`] that results from merging the `update_tip` chain into the
+/// `original_tip` chain, without mutating anything.
+///
+/// This is the same logic that [`LocalChain::apply_update`] uses to determine the changes to apply.
+/// Refer to [`LocalChain::apply_update`] for the connection rules and to [`Update`] for the meaning
+/// of `introduce_older_blocks`.
+///
+/// # Errors
+///
+/// An error will occur if the update chain does not correctly connect with the original chain.
+pub fn compute_merge_changeset(
+    original_tip: &CheckPoint,
+    update_tip: &CheckPoint,
+    introduce_older_blocks: bool,
+) -> Result<ChangeSet, CannotConnectError> {
+    merge_chains(
+        original_tip.clone(),
+        update_tip.clone(),
+        introduce_older_blocks,
+    )
+}
+
 fn merge_chains(
     original_tip: CheckPoint,
     update_tip: CheckPoint,
@@ -797,3 +2816,43 @@ fn merge_chains(
 
     Ok(changeset)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn block(height: u32) -> BlockId {
+        BlockId {
+            height,
+            hash: BlockHash::hash(&height.to_le_bytes()),
+        }
+    }
+
+    /// Appending blocks above the tip must link the new checkpoints onto the existing ones, rather
+    /// than rebuilding the checkpoint list.
+    #[test]
+    fn appending_reuses_existing_checkpoints() {
+        let mut chain = LocalChain::from_blocks((0..1000).map(|h| (h, block(h).hash)).collect())
+            .expect("has genesis");
+
+        let old_tip = chain.tip();
+        let update = old_tip.clone().push(block(1000)).expect("is above tip");
+        chain
+            .apply_update(update.into_update(false))
+            .expect("update must connect");
+        let prev = chain.tip().prev().expect("has prev");
+        assert!(Arc::ptr_eq(&prev.0, &old_tip.0));
+
+        let old_tip = chain.tip();
+        chain.insert_block(block(1001)).expect("is above tip");
+        let prev = chain.tip().prev().expect("has prev");
+        assert!(Arc::ptr_eq(&prev.0, &old_tip.0));
+
+        let old_tip = chain.tip();
+        chain
+            .apply_changeset(&[(1002, Some(block(1002).hash))].into())
+            .expect("is above tip");
+        let prev = chain.tip().prev().expect("has prev");
+        assert!(Arc::ptr_eq(&prev.0, &old_tip.0));
+    }
+}
@@ -98,12 +98,59 @@ pub trait Anchor: core::fmt::Debug + Clone + Eq + PartialOrd + Ord + core::hash:
     fn confirmation_height_upper_bound(&self) -> u32 {
         self.anchor_block().height
     }
+
+    /// Get the [`AnchorKind`] of the anchor, which describes what information the anchor records.
+    ///
+    /// The default definition returns [`AnchorKind::Block`]. This should be overridden by `Anchor`
+    /// implementations that record more than the anchor block.
+    fn kind(&self) -> AnchorKind {
+        AnchorKind::Block
+    }
+
+    /// Get a key which can be used to store the anchor in a key-value store.
+    ///
+    /// The default definition is the [`anchor_block`] height (4 bytes, big-endian) followed by the
+    /// 32-byte [`anchor_block`] hash. Sorting these keys by their bytes sorts the anchors by height
+    /// and then by hash. This can be overridden by the `Anchor` implementation to include more data.
+    ///
+    /// [`anchor_block`]: Self::anchor_block
+    fn storage_key(&self) -> [u8; 36] {
+        let block = self.anchor_block();
+        let mut key = [0_u8; 36];
+        key[..4].copy_from_slice(&block.height.to_be_bytes());
+        key[4..].copy_from_slice(block.hash.as_ref());
+        key
+    }
 }
 
 impl<'a, A: Anchor> Anchor for &'a A {
     fn anchor_block(&self) -> BlockId {
         <A as Anchor>::anchor_block(self)
     }
+
+    fn kind(&self) -> AnchorKind {
+        <A as Anchor>::kind(self)
+    }
+
+    fn storage_key(&self) -> [u8; 36] {
+        <A as Anchor>::storage_key(self)
+    }
+}
+
+/// Describes what information an [`Anchor`] records, as returned by [`Anchor::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, core::hash::Hash)]
+pub enum AnchorKind {
+    /// The anchor only records the anchor block (e.g. [`BlockId`]).
+    Block,
+    /// The anchor also records the confirmation height (e.g. [`ConfirmationHeightAnchor`]).
+    ///
+    /// [`ConfirmationHeightAnchor`]: crate::ConfirmationHeightAnchor
+    Height,
+    /// The anchor also records the confirmation height and time (e.g.
+    /// [`ConfirmationTimeHeightAnchor`]).
+    ///
+    /// [`ConfirmationTimeHeightAnchor`]: crate::ConfirmationTimeHeightAnchor
+    TimeHeight,
 }
 
 /// An [`Anchor`] that can be constructed from a given block, block height and transaction position
@@ -0,0 +1,715 @@
+#[macro_use]
+mod common;
+use bdk_chain::{
+    confirmed_balance, resolve_conflicting_spends, spendable_balance, Anchor, AnchorKind, BlockId,
+    ChainPosition, ChainPositionIterExt, ConfirmationHeightAnchor, ConfirmationTime,
+    ConfirmationTimeHeightAnchor, ConfirmedPosition, FinalityBucket, FullTxOut, MempoolPosition,
+    SpendStatus, UnixSeconds,
+};
+use bitcoin::{hashes::Hash, Amount, BlockHash, OutPoint, ScriptBuf, TxOut, Txid};
+use core::cmp::Ordering;
+
+#[test]
+fn chain_position_bump_last_seen() {
+    let mut position = ChainPosition::<BlockId>::Unconfirmed(100);
+    position.bump_last_seen(150);
+    assert_eq!(position, ChainPosition::Unconfirmed(150));
+
+    // sightings processed out of order (or repeated) never lower the timestamp
+    position.bump_last_seen(120);
+    assert_eq!(position, ChainPosition::Unconfirmed(150));
+    position.bump_last_seen(150);
+    assert_eq!(position, ChainPosition::Unconfirmed(150));
+
+    let mut position = ChainPosition::Confirmed(block_id!(1, "A"));
+    position.bump_last_seen(150);
+    assert_eq!(position, ChainPosition::Confirmed(block_id!(1, "A")));
+}
+
+fn txout(value: u64) -> TxOut {
+    TxOut {
+        value,
+        script_pubkey: ScriptBuf::new(),
+    }
+}
+
+fn outpoint(txid: &str, vout: u32) -> OutPoint {
+    OutPoint::new(Txid::hash(txid.as_bytes()), vout)
+}
+
+#[test]
+fn full_txout_new_and_builder() {
+    let position = ChainPosition::Confirmed(block_id!(1, "A"));
+    let utxo = FullTxOut::new(outpoint("tx", 0), txout(1_000), position);
+    assert_eq!(
+        utxo,
+        FullTxOut {
+            outpoint: outpoint("tx", 0),
+            txout: txout(1_000),
+            chain_position: position,
+            spent_by: None,
+            is_on_coinbase: false,
+        }
+    );
+
+    let spend = (ChainPosition::Unconfirmed(100), Txid::hash(b"spend"));
+    let utxo = utxo.with_spent_by(spend.0, spend.1).with_coinbase(true);
+    assert_eq!(utxo.spent_by, Some(spend));
+    assert!(utxo.is_on_coinbase);
+    assert!(!utxo.with_coinbase(false).is_on_coinbase);
+}
+
+#[test]
+fn mempool_position_expiry() {
+    let position = MempoolPosition::<BlockId>::Unconfirmed {
+        last_seen: 100,
+        expires_at: Some(200),
+    };
+    assert!(!position.is_confirmed());
+    assert!(!position.is_expired(199));
+    // the position expires exactly at the deadline
+    assert!(position.is_expired(200));
+    assert!(position.is_expired(201));
+
+    let no_expiry = MempoolPosition::<BlockId>::Unconfirmed {
+        last_seen: 100,
+        expires_at: None,
+    };
+    assert!(!no_expiry.is_expired(u64::MAX));
+
+    let confirmed = MempoolPosition::Confirmed(block_id!(1, "A"));
+    assert!(confirmed.is_confirmed());
+    assert!(!confirmed.is_expired(u64::MAX));
+}
+
+#[test]
+fn mempool_position_conversions() {
+    assert_eq!(
+        MempoolPosition::from(ChainPosition::<BlockId>::Unconfirmed(100)),
+        MempoolPosition::Unconfirmed {
+            last_seen: 100,
+            expires_at: None,
+        }
+    );
+    assert_eq!(
+        MempoolPosition::from(ChainPosition::Confirmed(block_id!(1, "A"))),
+        MempoolPosition::Confirmed(block_id!(1, "A"))
+    );
+
+    // converting back drops the expiry
+    let position = MempoolPosition::<BlockId>::Unconfirmed {
+        last_seen: 100,
+        expires_at: Some(200),
+    };
+    assert_eq!(
+        ChainPosition::from(position),
+        ChainPosition::Unconfirmed(100)
+    );
+}
+
+fn time_anchor(height: u32, time: u64) -> ConfirmationTimeHeightAnchor {
+    ConfirmationTimeHeightAnchor {
+        anchor_block: BlockId {
+            height,
+            hash: BlockHash::hash(&height.to_le_bytes()),
+        },
+        confirmation_height: height,
+        confirmation_time: time,
+    }
+}
+
+#[test]
+fn confirmation_time_height_anchor_age() {
+    let anchor = time_anchor(1, 1_000);
+    assert_eq!(anchor.age(1_500), 500);
+    assert_eq!(anchor.age(1_000), 0);
+    // a reference time before the confirmation time saturates to zero
+    assert_eq!(anchor.age(999), 0);
+    assert_eq!(anchor.age(0), 0);
+}
+
+#[test]
+fn chain_position_confirmed_view() {
+    let positions = [
+        ChainPosition::Confirmed(block_id!(1, "A")),
+        ChainPosition::Unconfirmed(100),
+        ChainPosition::Confirmed(block_id!(2, "B")),
+    ];
+    let confirmed = positions
+        .into_iter()
+        .filter_map(ChainPosition::confirmed)
+        .collect::<Vec<ConfirmedPosition<BlockId>>>();
+    assert_eq!(confirmed.len(), 2);
+    assert_eq!(confirmed[0].anchor(), &block_id!(1, "A"));
+    assert_eq!(confirmed[1].into_anchor(), block_id!(2, "B"));
+
+    // converting back results in the original position
+    assert_eq!(ChainPosition::from(confirmed[0]), positions[0]);
+}
+
+#[test]
+fn chain_position_ordering_key() {
+    let height_anchor = |confirmation_height: u32| ConfirmationHeightAnchor {
+        anchor_block: block_id!(10, "J"),
+        confirmation_height,
+    };
+    assert_eq!(
+        ChainPosition::Confirmed(height_anchor(3)).ordering_key(),
+        (0, 3)
+    );
+    assert_eq!(
+        ChainPosition::<ConfirmationHeightAnchor>::Unconfirmed(100).ordering_key(),
+        (1, u64::MAX - 100)
+    );
+
+    // confirmed positions sort first (by ascending height), then the most recently seen
+    let mut positions = vec![
+        ChainPosition::Unconfirmed(100),
+        ChainPosition::Confirmed(height_anchor(5)),
+        ChainPosition::Unconfirmed(200),
+        ChainPosition::Confirmed(height_anchor(3)),
+        ChainPosition::Unconfirmed(0),
+    ];
+    positions.sort_by_key(ChainPosition::ordering_key);
+    assert_eq!(
+        positions,
+        vec![
+            ChainPosition::Confirmed(height_anchor(3)),
+            ChainPosition::Confirmed(height_anchor(5)),
+            ChainPosition::Unconfirmed(200),
+            ChainPosition::Unconfirmed(100),
+            ChainPosition::Unconfirmed(0),
+        ]
+    );
+}
+
+#[test]
+fn confirmation_time_recency_cmp() {
+    let mut times = vec![
+        ConfirmationTime::Confirmed {
+            height: 1,
+            time: 100,
+        },
+        ConfirmationTime::Unconfirmed { last_seen: 300 },
+        ConfirmationTime::Confirmed {
+            height: 2,
+            time: 200,
+        },
+        ConfirmationTime::Unconfirmed { last_seen: 400 },
+        ConfirmationTime::Confirmed {
+            height: 2,
+            time: 150,
+        },
+    ];
+    times.sort_by(ConfirmationTime::recency_cmp);
+    assert_eq!(
+        times,
+        vec![
+            ConfirmationTime::Unconfirmed { last_seen: 400 },
+            ConfirmationTime::Unconfirmed { last_seen: 300 },
+            ConfirmationTime::Confirmed {
+                height: 2,
+                time: 200,
+            },
+            ConfirmationTime::Confirmed {
+                height: 2,
+                time: 150,
+            },
+            ConfirmationTime::Confirmed {
+                height: 1,
+                time: 100,
+            },
+        ]
+    );
+
+    let pending = ConfirmationTime::Unconfirmed { last_seen: 0 };
+    assert_eq!(pending.recency_cmp(&pending), Ordering::Equal);
+    // unlike the derived ordering, any unconfirmed time is more recent than a confirmed time
+    let confirmed = ConfirmationTime::Confirmed {
+        height: u32::MAX,
+        time: u64::MAX,
+    };
+    assert_eq!(pending.recency_cmp(&confirmed), Ordering::Less);
+    assert_eq!(confirmed.recency_cmp(&pending), Ordering::Greater);
+}
+
+#[test]
+fn full_txout_relative_height_satisfied() {
+    let utxo = FullTxOut::new(
+        outpoint("tx", 0),
+        txout(1_000),
+        ChainPosition::Confirmed(time_anchor(10, 0)),
+    );
+    // confirmed at the tip is one confirmation
+    assert!(utxo.relative_height_satisfied(10, 1));
+    assert!(!utxo.relative_height_satisfied(10, 2));
+    assert!(utxo.relative_height_satisfied(14, 5));
+    assert!(!utxo.relative_height_satisfied(14, 6));
+    // confirmed above the tip is no confirmations
+    assert!(!utxo.relative_height_satisfied(9, 1));
+    assert!(utxo.relative_height_satisfied(9, 0));
+
+    let unconfirmed = FullTxOut::new(
+        outpoint("tx", 1),
+        txout(1_000),
+        ChainPosition::<ConfirmationTimeHeightAnchor>::Unconfirmed(100),
+    );
+    assert!(!unconfirmed.relative_height_satisfied(u32::MAX, 0));
+}
+
+#[test]
+fn unix_seconds() {
+    let secs = UnixSeconds::from(1_700_000_000);
+    assert_eq!(secs.as_secs(), 1_700_000_000);
+    assert_eq!(u64::from(secs), 1_700_000_000);
+    assert_eq!(UnixSeconds::default().as_secs(), 0);
+
+    // the unconfirmed constructors accept both raw seconds and `UnixSeconds`
+    assert_eq!(
+        ChainPosition::<BlockId>::unconfirmed(secs),
+        ChainPosition::Unconfirmed(1_700_000_000)
+    );
+    assert_eq!(
+        ChainPosition::<BlockId>::unconfirmed(1_700_000_000),
+        ChainPosition::Unconfirmed(1_700_000_000)
+    );
+    assert_eq!(
+        ConfirmationTime::unconfirmed(secs),
+        ConfirmationTime::Unconfirmed {
+            last_seen: 1_700_000_000
+        }
+    );
+}
+
+#[test]
+fn test_resolve_conflicting_spends() {
+    let txid = |s: &str| Txid::hash(s.as_bytes());
+    let confirmed_low = (ChainPosition::Confirmed(time_anchor(5, 0)), txid("low"));
+    let confirmed_high = (ChainPosition::Confirmed(time_anchor(7, 0)), txid("high"));
+    let seen_early = (ChainPosition::Unconfirmed(100), txid("early"));
+    let seen_late = (ChainPosition::Unconfirmed(200), txid("late"));
+
+    // a confirmed spend wins over an unconfirmed spend, regardless of the argument order
+    assert_eq!(
+        resolve_conflicting_spends(seen_late, confirmed_high),
+        confirmed_high
+    );
+    assert_eq!(
+        resolve_conflicting_spends(confirmed_high, seen_late),
+        confirmed_high
+    );
+    // the deeper confirmation wins
+    assert_eq!(
+        resolve_conflicting_spends(confirmed_high, confirmed_low),
+        confirmed_low
+    );
+    // the most recently seen unconfirmed spend wins
+    assert_eq!(resolve_conflicting_spends(seen_early, seen_late), seen_late);
+
+    // ties keep the first spend
+    let confirmed_low_other = (ChainPosition::Confirmed(time_anchor(5, 0)), txid("other"));
+    assert_eq!(
+        resolve_conflicting_spends(confirmed_low, confirmed_low_other),
+        confirmed_low
+    );
+    let seen_early_other = (ChainPosition::Unconfirmed(100), txid("other"));
+    assert_eq!(
+        resolve_conflicting_spends(seen_early_other, seen_early),
+        seen_early_other
+    );
+}
+
+#[test]
+fn anchor_kind() {
+    let block = block_id!(1, "A");
+    let height_anchor = ConfirmationHeightAnchor {
+        anchor_block: block,
+        confirmation_height: 1,
+    };
+    let time_height_anchor = time_anchor(1, 100);
+
+    assert_eq!(block.kind(), AnchorKind::Block);
+    assert_eq!(height_anchor.kind(), AnchorKind::Height);
+    assert_eq!(time_height_anchor.kind(), AnchorKind::TimeHeight);
+    // references delegate to the referenced anchor
+    assert_eq!(Anchor::kind(&&time_height_anchor), AnchorKind::TimeHeight);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn unconfirmed_now() {
+    let before = UnixSeconds::now().as_secs();
+    let position = ChainPosition::<BlockId>::unconfirmed_now();
+    let confirmation_time = ConfirmationTime::unconfirmed_now();
+    let after = UnixSeconds::now().as_secs();
+    assert!(before > 0);
+
+    match position {
+        ChainPosition::Unconfirmed(last_seen) => assert!((before..=after).contains(&last_seen)),
+        ChainPosition::Confirmed(_) => panic!("must be unconfirmed"),
+    }
+    match confirmation_time {
+        ConfirmationTime::Unconfirmed { last_seen } => {
+            assert!((before..=after).contains(&last_seen))
+        }
+        ConfirmationTime::Confirmed { .. } => panic!("must be unconfirmed"),
+    }
+}
+
+#[test]
+fn spendable_and_confirmed_balance() {
+    let confirmed = |vout: u32, value: u64, height: u32| {
+        FullTxOut::new(
+            outpoint("tx", vout),
+            txout(value),
+            ChainPosition::Confirmed(time_anchor(height, 0)),
+        )
+    };
+    let spend = |height: u32| ChainPosition::Confirmed(time_anchor(height, 0));
+    let utxos = vec![
+        confirmed(0, 1_000, 5),
+        FullTxOut::new(
+            outpoint("tx", 1),
+            txout(2_000),
+            ChainPosition::Unconfirmed(100),
+        ),
+        // spent within the tip
+        confirmed(2, 4_000, 5).with_spent_by(spend(8), Txid::hash(b"spend")),
+        // immature coinbase
+        confirmed(3, 8_000, 5).with_coinbase(true),
+        // confirmed above the tip
+        confirmed(4, 16_000, 12),
+        // spent above the tip
+        confirmed(5, 32_000, 5).with_spent_by(spend(11), Txid::hash(b"spend")),
+    ];
+
+    let tip = 10;
+    assert_eq!(
+        spendable_balance(utxos.clone(), tip),
+        Amount::from_sat(1_000 + 32_000)
+    );
+    assert_eq!(
+        confirmed_balance(utxos),
+        Amount::from_sat(1_000 + 4_000 + 8_000 + 16_000 + 32_000)
+    );
+
+    let no_utxos = Vec::<FullTxOut<ConfirmationTimeHeightAnchor>>::new();
+    assert_eq!(spendable_balance(no_utxos.clone(), tip), Amount::ZERO);
+    assert_eq!(confirmed_balance(no_utxos), Amount::ZERO);
+}
+
+#[test]
+fn chain_position_deepest() {
+    let low = ChainPosition::Confirmed(time_anchor(5, 0));
+    let high = ChainPosition::Confirmed(time_anchor(7, 0));
+    let seen_early = ChainPosition::Unconfirmed(100);
+    let seen_late = ChainPosition::Unconfirmed(200);
+
+    assert_eq!(high.deepest(low), low);
+    assert_eq!(low.deepest(high), low);
+    assert_eq!(seen_late.deepest(high), high);
+    assert_eq!(high.deepest(seen_late), high);
+    assert_eq!(seen_early.deepest(seen_late), seen_late);
+
+    // ties keep `self`
+    let low_other = ChainPosition::Confirmed(ConfirmationTimeHeightAnchor {
+        confirmation_time: 1,
+        ..time_anchor(5, 0)
+    });
+    assert_eq!(low.deepest(low_other), low);
+    assert_eq!(low_other.deepest(low), low_other);
+}
+
+#[test]
+fn anchor_conversions() {
+    let anchor = ConfirmationTimeHeightAnchor {
+        anchor_block: block_id!(10, "J"),
+        confirmation_height: 8,
+        confirmation_time: 100,
+    };
+    assert_eq!(BlockId::from(anchor), block_id!(10, "J"));
+    assert_eq!(
+        ConfirmationHeightAnchor::from(anchor),
+        ConfirmationHeightAnchor {
+            anchor_block: block_id!(10, "J"),
+            confirmation_height: 8,
+        }
+    );
+    // the conversion keeps the anchor block of the original anchor
+    assert_eq!(
+        BlockId::from(ConfirmationHeightAnchor::from(anchor)),
+        anchor.anchor_block()
+    );
+}
+
+#[test]
+fn full_txout_cmp_by_outpoint() {
+    let a = FullTxOut::new(
+        outpoint("tx", 0),
+        txout(1_000),
+        ChainPosition::Confirmed(time_anchor(5, 0)),
+    );
+    let a_unconfirmed = FullTxOut::new(
+        outpoint("tx", 0),
+        txout(2_000),
+        ChainPosition::Unconfirmed(100),
+    );
+    let b = FullTxOut::new(
+        outpoint("tx", 1),
+        txout(1_000),
+        ChainPosition::Unconfirmed(100),
+    );
+
+    // records of the same outpoint are equal by outpoint, but not structurally
+    assert_eq!(a.cmp_by_outpoint(&a_unconfirmed), Ordering::Equal);
+    assert_ne!(a.cmp(&a_unconfirmed), Ordering::Equal);
+    assert_eq!(a.cmp_by_outpoint(&b), Ordering::Less);
+    assert_eq!(b.cmp_by_outpoint(&a_unconfirmed), Ordering::Greater);
+}
+
+#[test]
+fn chain_position_same_confirmation() {
+    let height_anchor = |anchor_block: BlockId, confirmation_height: u32| {
+        ChainPosition::Confirmed(ConfirmationHeightAnchor {
+            anchor_block,
+            confirmation_height,
+        })
+    };
+    let a = height_anchor(block_id!(10, "J"), 8);
+
+    // different anchor blocks with the same confirmation height are the same confirmation
+    assert!(a.same_confirmation(&height_anchor(block_id!(12, "L"), 8)));
+    assert_ne!(a, height_anchor(block_id!(12, "L"), 8));
+    assert!(!a.same_confirmation(&height_anchor(block_id!(10, "J"), 9)));
+
+    let unconfirmed = ChainPosition::Unconfirmed(100);
+    assert!(unconfirmed.same_confirmation(&ChainPosition::Unconfirmed(100)));
+    assert!(!unconfirmed.same_confirmation(&ChainPosition::Unconfirmed(101)));
+    assert!(!unconfirmed.same_confirmation(&a));
+    assert!(!a.same_confirmation(&unconfirmed));
+}
+
+#[test]
+fn anchor_storage_key() {
+    let block = block_id!(0x0102, "A");
+    let key = block.storage_key();
+    assert_eq!(key[..4], [0x00, 0x00, 0x01, 0x02]);
+    assert_eq!(key[4..], block.hash[..]);
+
+    // anchors with the same anchor block have the same key
+    let height_anchor = ConfirmationHeightAnchor {
+        anchor_block: block,
+        confirmation_height: 0x0100,
+    };
+    assert_eq!(height_anchor.storage_key(), key);
+    assert_eq!(Anchor::storage_key(&&height_anchor), key);
+
+    // sorting the keys sorts the anchors by height (then by hash)
+    let mut blocks = [
+        block_id!(256, "B"),
+        block_id!(1, "C"),
+        block_id!(1, "A"),
+        block_id!(0, "D"),
+    ];
+    let mut keys = blocks.iter().map(Anchor::storage_key).collect::<Vec<_>>();
+    keys.sort();
+    blocks.sort();
+    assert_eq!(
+        keys,
+        blocks.iter().map(Anchor::storage_key).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn chain_position_unconfirm() {
+    // an orphaned confirmation becomes unconfirmed with the given last-seen
+    let position = ChainPosition::Confirmed(block_id!(1, "A"));
+    assert_eq!(position.unconfirm(100), ChainPosition::Unconfirmed(100));
+
+    // the last-seen of an unconfirmed position is never lowered
+    let position = ChainPosition::<BlockId>::Unconfirmed(100);
+    assert_eq!(position.unconfirm(150), ChainPosition::Unconfirmed(150));
+    assert_eq!(position.unconfirm(100), ChainPosition::Unconfirmed(100));
+    assert_eq!(position.unconfirm(50), ChainPosition::Unconfirmed(100));
+}
+
+#[test]
+fn chain_position_status_label() {
+    let position = ChainPosition::Confirmed(block_id!(10, "A"));
+    assert_eq!(position.status_label(15), "Confirmed (6)");
+    // a position at the tip has one confirmation
+    assert_eq!(position.status_label(10), "Confirmed (1)");
+    // a position above the tip has no confirmations
+    assert_eq!(position.status_label(9), "Confirmed (0)");
+
+    // the confirmation height of the anchor is used rather than the anchor block
+    let position = ChainPosition::Confirmed(ConfirmationHeightAnchor {
+        anchor_block: block_id!(12, "B"),
+        confirmation_height: 10,
+    });
+    assert_eq!(position.status_label(12), "Confirmed (3)");
+
+    let position = ChainPosition::<BlockId>::Unconfirmed(100);
+    assert_eq!(position.status_label(15), "Unconfirmed");
+}
+
+#[test]
+fn chain_position_into_confirmation_times() {
+    let positions = vec![
+        ChainPosition::Confirmed(time_anchor(2, 200)),
+        ChainPosition::Unconfirmed(300),
+        ChainPosition::Confirmed(time_anchor(1, 100)),
+    ];
+
+    let confirmation_times = positions.into_iter().into_confirmation_times();
+    assert_eq!(confirmation_times.size_hint(), (3, Some(3)));
+    assert_eq!(
+        confirmation_times.collect::<Vec<_>>(),
+        vec![
+            ConfirmationTime::Confirmed {
+                height: 2,
+                time: 200
+            },
+            ConfirmationTime::Unconfirmed { last_seen: 300 },
+            ConfirmationTime::Confirmed {
+                height: 1,
+                time: 100
+            },
+        ]
+    );
+
+    let mut empty = core::iter::empty().into_confirmation_times();
+    assert_eq!(empty.size_hint(), (0, Some(0)));
+    assert_eq!(empty.next(), None);
+}
+
+#[test]
+fn chain_position_finality_bucket() {
+    let position = ChainPosition::Confirmed(block_id!(10, "A"));
+    // each `(tip, expected)` case is at a threshold of the buckets
+    let cases = [
+        (9, FinalityBucket::Unconfirmed),
+        (10, FinalityBucket::Low),
+        (11, FinalityBucket::Low),
+        (12, FinalityBucket::Medium),
+        (14, FinalityBucket::Medium),
+        (15, FinalityBucket::Final),
+        (u32::MAX, FinalityBucket::Final),
+    ];
+    for (tip, expected) in cases {
+        assert_eq!(position.finality_bucket(tip), expected, "tip: {}", tip);
+    }
+
+    let position = ChainPosition::<BlockId>::Unconfirmed(100);
+    assert_eq!(position.finality_bucket(15), FinalityBucket::Unconfirmed);
+
+    // buckets are ordered from the least to the most final
+    assert!(FinalityBucket::Unconfirmed < FinalityBucket::Low);
+    assert!(FinalityBucket::Low < FinalityBucket::Medium);
+    assert!(FinalityBucket::Medium < FinalityBucket::Final);
+}
+
+#[test]
+fn full_txout_spend_status() {
+    let utxo = FullTxOut::new(
+        outpoint("tx", 0),
+        txout(1_000),
+        ChainPosition::Confirmed(block_id!(1, "A")),
+    );
+    assert_eq!(utxo.spend_status(10), SpendStatus::Unspent);
+
+    let spend_txid = Txid::hash(b"spend");
+    let spent = utxo
+        .clone()
+        .with_spent_by(ChainPosition::Unconfirmed(100), spend_txid);
+    assert_eq!(
+        spent.spend_status(10),
+        SpendStatus::SpentUnconfirmed(spend_txid)
+    );
+
+    let spent = utxo.with_spent_by(ChainPosition::Confirmed(block_id!(5, "B")), spend_txid);
+    let confirmations_at = |tip| match spent.spend_status(tip) {
+        SpendStatus::SpentConfirmed {
+            txid,
+            confirmations,
+        } => {
+            assert_eq!(txid, spend_txid);
+            confirmations
+        }
+        status => panic!("unexpected spend status: {:?}", status),
+    };
+    assert_eq!(confirmations_at(10), 6);
+    // a spend at the tip has one confirmation
+    assert_eq!(confirmations_at(5), 1);
+    // a spend above the tip has no confirmations
+    assert_eq!(confirmations_at(4), 0);
+}
+
+#[test]
+fn confirmation_time_into_chain_position() {
+    let hash = BlockHash::hash(b"A");
+    let confirmed = ConfirmationTime::Confirmed {
+        height: 2,
+        time: 200,
+    };
+    let position = confirmed.into_chain_position(hash);
+    assert_eq!(
+        position,
+        ChainPosition::Confirmed(ConfirmationTimeHeightAnchor {
+            anchor_block: BlockId { height: 2, hash },
+            confirmation_height: 2,
+            confirmation_time: 200,
+        })
+    );
+    assert_eq!(ConfirmationTime::from(position), confirmed);
+
+    let unconfirmed = ConfirmationTime::Unconfirmed { last_seen: 300 };
+    let position = unconfirmed.into_chain_position(hash);
+    assert_eq!(position, ChainPosition::Unconfirmed(300));
+    assert_eq!(ConfirmationTime::from(position), unconfirmed);
+}
+
+#[test]
+fn full_txout_confirmation_block() {
+    let anchor = ConfirmationHeightAnchor {
+        anchor_block: block_id!(12, "B"),
+        confirmation_height: 10,
+    };
+    let position = ChainPosition::Confirmed(anchor);
+    assert_eq!(position.anchor(), Some(&anchor));
+
+    // the anchor block is returned, not the confirmation block
+    let utxo = FullTxOut::new(outpoint("tx", 0), txout(1_000), position);
+    assert_eq!(utxo.confirmation_block(), Some(block_id!(12, "B")));
+
+    let position = ChainPosition::<ConfirmationHeightAnchor>::Unconfirmed(100);
+    assert_eq!(position.anchor(), None);
+    let utxo = FullTxOut::new(outpoint("tx", 0), txout(1_000), position);
+    assert_eq!(utxo.confirmation_block(), None);
+}
+
+#[test]
+fn chain_position_confirmed_in_window() {
+    let position = ChainPosition::Confirmed(time_anchor(1, 100));
+    // `start` is inclusive and `end` is exclusive
+    assert!(position.confirmed_in_window(100, 200));
+    assert!(position.confirmed_in_window(99, 101));
+    assert!(!position.confirmed_in_window(0, 100));
+    assert!(!position.confirmed_in_window(101, 200));
+    // an empty window contains nothing
+    assert!(!position.confirmed_in_window(100, 100));
+
+    // consecutive windows never both contain the same position
+    let windows = [(0, 100), (100, 200), (200, 300)];
+    for time in [0, 99, 100, 199, 200, 299] {
+        let position = ChainPosition::Confirmed(time_anchor(1, time));
+        let containing = windows
+            .iter()
+            .filter(|(start, end)| position.confirmed_in_window(*start, *end))
+            .count();
+        assert_eq!(containing, 1, "time: {}", time);
+    }
+
+    let position = ChainPosition::Unconfirmed(100);
+    assert!(!position.confirmed_in_window(0, u64::MAX));
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "serde")]
+
+#[macro_use]
+mod common;
+use bdk_chain::{ChainPosition, ConfirmationTimeHeightAnchor, FullTxOut};
+use bitcoin::{hashes::Hash, OutPoint, ScriptBuf, TxOut, Txid};
+
+fn full_txout() -> FullTxOut<ConfirmationTimeHeightAnchor> {
+    FullTxOut {
+        outpoint: OutPoint::new(Txid::hash(b"funding"), 1),
+        txout: TxOut {
+            value: 50_000,
+            script_pubkey: ScriptBuf::from_bytes(vec![0x51]),
+        },
+        chain_position: ChainPosition::Confirmed(ConfirmationTimeHeightAnchor {
+            anchor_block: block_id!(10, "J"),
+            confirmation_height: 8,
+            confirmation_time: 1_700_000_000,
+        }),
+        spent_by: Some((
+            ChainPosition::Unconfirmed(1_700_000_100),
+            Txid::hash(b"spending"),
+        )),
+        is_on_coinbase: false,
+    }
+}
+
+#[test]
+fn full_txout_serde_json() {
+    let txout = full_txout();
+    let json = serde_json::to_string(&txout).expect("must serialize");
+    let decoded: FullTxOut<ConfirmationTimeHeightAnchor> =
+        serde_json::from_str(&json).expect("must deserialize");
+    assert_eq!(decoded, txout);
+
+    let value = serde_json::to_value(&txout).expect("must serialize");
+    assert_eq!(
+        value["spent_by"],
+        serde_json::json!([
+            { "status": "unconfirmed", "last_seen": 1_700_000_100 },
+            Txid::hash(b"spending")
+        ])
+    );
+}
+
+#[test]
+fn chain_position_serde_json_shape() {
+    let confirmed = ChainPosition::Confirmed(ConfirmationTimeHeightAnchor {
+        anchor_block: block_id!(10, "J"),
+        confirmation_height: 8,
+        confirmation_time: 1_700_000_000,
+    });
+    let value = serde_json::to_value(confirmed).expect("must serialize");
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "status": "confirmed",
+            "anchor_block": { "height": 10, "hash": block_id!(10, "J").hash },
+            "confirmation_height": 8,
+            "confirmation_time": 1_700_000_000,
+        })
+    );
+    assert_eq!(
+        serde_json::from_value::<ChainPosition<ConfirmationTimeHeightAnchor>>(value)
+            .expect("must deserialize"),
+        confirmed
+    );
+
+    let unconfirmed = ChainPosition::<ConfirmationTimeHeightAnchor>::Unconfirmed(1_700_000_100);
+    let value = serde_json::to_value(unconfirmed).expect("must serialize");
+    assert_eq!(
+        value,
+        serde_json::json!({ "status": "unconfirmed", "last_seen": 1_700_000_100 })
+    );
+    assert_eq!(
+        serde_json::from_value::<ChainPosition<ConfirmationTimeHeightAnchor>>(value)
+            .expect("must deserialize"),
+        unconfirmed
+    );
+}
+
+#[test]
+fn full_txout_serde_bincode() {
+    let txout = full_txout();
+    let bytes = bincode::serialize(&txout).expect("must serialize");
+    let decoded: FullTxOut<ConfirmationTimeHeightAnchor> =
+        bincode::deserialize(&bytes).expect("must deserialize");
+    assert_eq!(decoded, txout);
+}
@@ -1,9 +1,13 @@
 use bdk_chain::{
+    filter_canonical,
     local_chain::{
-        AlterCheckPointError, ApplyHeaderError, CannotConnectError, ChangeSet, CheckPoint,
-        LocalChain, MissingGenesisError, Update,
+        compute_merge_changeset, summarize_changeset, AlterCheckPointError, ApplyBlockIdsError,
+        ApplyChangeSetError, ApplyHeaderError, ApplyUpdateError, CannotConnectError, ChainEvent,
+        ChainStats, ChangeSet, CheckPoint, DecodeError, FastForwardError, FromBlocksSortedError,
+        IntegrityReport, LocalChain, MissingGenesisError, ReplaceError, TipChange,
+        TrustedCheckpointViolation, Update, UpdateReport,
     },
-    BlockId,
+    BlockId, ChainOracle,
 };
 use bitcoin::{block::Header, hashes::Hash, BlockHash};
 
@@ -30,6 +34,34 @@ enum ExpectedResult<'a> {
 impl<'a> TestLocalChain<'a> {
     fn run(mut self) {
         println!("[TestLocalChain] test: {}", self.name);
+
+        // applying the update blocks as a stream should give the same result
+        if self.update.introduce_older_blocks {
+            let mut chain = self.chain.clone();
+            let mut blocks = self
+                .update
+                .tip
+                .iter()
+                .map(|cp| cp.block_id())
+                .collect::<Vec<_>>();
+            blocks.reverse();
+            let stream_result = chain.apply_block_ids(blocks.into_iter());
+            let mut exp_chain = self.chain.clone();
+            assert_eq!(
+                stream_result,
+                exp_chain
+                    .apply_update(self.update.clone())
+                    .map_err(ApplyBlockIdsError::CannotConnect),
+                "{}: unexpected `apply_block_ids` result",
+                self.name
+            );
+            assert_eq!(
+                chain, exp_chain,
+                "{}: unexpected `apply_block_ids` chain",
+                self.name
+            );
+        }
+
         let got_changeset = match self.chain.apply_update(self.update) {
             Ok(changeset) => changeset,
             Err(got_err) => {
@@ -169,13 +201,22 @@ fn update_local_chain() {
         },
         TestLocalChain {
             name: "fix blockhash before agreement point",
-            chain: local_chain![(0, h!("im-wrong")), (1, h!("we-agree"))],
-            update: chain_update![(0, h!("fix")), (1, h!("we-agree"))],
+            chain: local_chain![(0, h!("_")), (1, h!("im-wrong")), (2, h!("we-agree"))],
+            update: chain_update![(0, h!("_")), (1, h!("fix")), (2, h!("we-agree"))],
             exp: ExpectedResult::Ok {
-                changeset: &[(0, Some(h!("fix")))],
-                init_changeset: &[(0, Some(h!("fix"))), (1, Some(h!("we-agree")))],
+                changeset: &[(1, Some(h!("fix")))],
+                init_changeset: &[(0, Some(h!("_"))), (1, Some(h!("fix"))), (2, Some(h!("we-agree")))],
             },
         },
+        // The genesis block is never invalidated, even before the agreement point.
+        TestLocalChain {
+            name: "refuse to fix genesis blockhash before agreement point",
+            chain: local_chain![(0, h!("im-wrong")), (1, h!("we-agree"))],
+            update: chain_update![(0, h!("fix")), (1, h!("we-agree"))],
+            exp: ExpectedResult::Err(CannotConnectError {
+                try_include_height: 0,
+            }),
+        },
         // B and C are in both chain and update
         //        | 0 | 1 | 2 | 3 | 4
         // chain  | _       B   C
@@ -679,3 +720,2027 @@ fn local_chain_apply_header_connected_to() {
         assert_eq!(result, exp_result, "[{}:{}] unexpected result", i, t.name);
     }
 }
+
+#[test]
+fn checkpoint_ordering() {
+    let a = CheckPoint::new(block_id!(0, "A"))
+        .push(block_id!(1, "B"))
+        .unwrap();
+    let b = CheckPoint::new(block_id!(1, "B"));
+    let c = CheckPoint::new(block_id!(1, "C"));
+    let d = CheckPoint::new(block_id!(2, "D"));
+
+    // checkpoints of the same block are equal regardless of their history
+    assert_eq!(a, b);
+    assert_eq!(a.cmp(&b), core::cmp::Ordering::Equal);
+
+    // competing blocks of the same height are ordered by hash
+    assert_ne!(b, c);
+    assert_eq!(b.cmp(&c), b.block_id().cmp(&c.block_id()));
+
+    let sorted = [d.clone(), c.clone(), b.clone()]
+        .into_iter()
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .map(|cp| cp.height())
+        .collect::<Vec<_>>();
+    assert_eq!(sorted, vec![1, 1, 2]);
+}
+
+#[test]
+fn local_chain_is_genesis_of() {
+    let genesis_hash = bitcoin::constants::genesis_block(bitcoin::Network::Testnet).block_hash();
+    let (chain, _) = LocalChain::from_genesis_hash(genesis_hash);
+    assert!(chain.is_genesis_of(bitcoin::Network::Testnet));
+    assert!(!chain.is_genesis_of(bitcoin::Network::Bitcoin));
+    assert!(!local_chain![(0, h!("_"))].is_genesis_of(bitcoin::Network::Testnet));
+}
+
+#[test]
+fn local_chain_fingerprint() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    assert_eq!(
+        chain.fingerprint(),
+        local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))].fingerprint()
+    );
+    assert_ne!(
+        chain.fingerprint(),
+        local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C'"))].fingerprint()
+    );
+    assert_ne!(
+        chain.fingerprint(),
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("C"))].fingerprint()
+    );
+    assert_ne!(
+        chain.fingerprint(),
+        local_chain![(0, h!("_")), (1, h!("A"))].fingerprint()
+    );
+}
+
+#[test]
+fn local_chain_apply_update_of_own_tip() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    let exp_chain = chain.clone();
+    for introduce_older_blocks in [false, true] {
+        let update = chain.tip().into_update(introduce_older_blocks);
+        assert_eq!(chain.apply_update(update), Ok(ChangeSet::default()));
+        assert_eq!(chain, exp_chain);
+    }
+}
+
+#[test]
+fn local_chain_summarize_changeset() {
+    struct TestCase<'a> {
+        changeset: &'a [(u32, Option<BlockHash>)],
+        exp_summary: &'a str,
+    }
+
+    let test_cases = [
+        TestCase {
+            changeset: &[],
+            exp_summary: "no changes",
+        },
+        TestCase {
+            changeset: &[(2, Some(h!("B")))],
+            exp_summary: "added 1 block (2)",
+        },
+        TestCase {
+            changeset: &[
+                (800141, None),
+                (800142, Some(h!("B"))),
+                (800143, Some(h!("C"))),
+                (800144, Some(h!("D"))),
+            ],
+            exp_summary: "added 3 blocks (800142..800144), removed 1 block (800141)",
+        },
+        TestCase {
+            changeset: &[
+                (1, None),
+                (2, None),
+                (4, None),
+                (7, Some(h!("G"))),
+                (9, Some(h!("I"))),
+                (10, Some(h!("J"))),
+            ],
+            exp_summary: "added 3 blocks (7, 9..10), removed 3 blocks (1..2, 4)",
+        },
+    ];
+
+    for (i, t) in test_cases.into_iter().enumerate() {
+        let changeset = t.changeset.iter().cloned().collect::<ChangeSet>();
+        assert_eq!(
+            summarize_changeset(&changeset),
+            t.exp_summary,
+            "[{}] unexpected summary",
+            i
+        );
+    }
+}
+
+#[test]
+fn local_chain_into_changeset() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    let init_changeset = chain.initial_changeset();
+    assert_eq!(
+        init_changeset,
+        [(0, Some(h!("_"))), (1, Some(h!("A"))), (3, Some(h!("C")))].into()
+    );
+    assert_eq!(chain.clone().into_changeset(), init_changeset);
+
+    // the changeset reconstructs the same chain
+    assert_eq!(
+        LocalChain::from_changeset(chain.clone().into_changeset()),
+        Ok(chain)
+    );
+}
+
+#[test]
+fn checkpoint_is_tip_of() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    assert!(chain.tip().is_tip_of(&chain));
+
+    // same blocks, but a different underlying list
+    let tip = CheckPoint::from_block_ids([block_id!(0, "_"), block_id!(1, "A"), block_id!(3, "C")])
+        .unwrap();
+    assert!(tip.is_tip_of(&chain));
+
+    // same tip block, but different history
+    let tip = CheckPoint::from_block_ids([block_id!(0, "_"), block_id!(2, "B"), block_id!(3, "C")])
+        .unwrap();
+    assert!(!tip.is_tip_of(&chain));
+
+    // a stale tip
+    let stale_tip = chain.tip().prev().unwrap();
+    assert!(!stale_tip.is_tip_of(&chain));
+}
+
+#[test]
+fn local_chain_set_tip_restores_prev_tip() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    let exp_chain = chain.clone();
+
+    let (changeset, prev_tip) = chain
+        .apply_update_returning_prev(chain_update![
+            (0, h!("_")),
+            (1, h!("A")),
+            (2, h!("B'")),
+            (3, h!("C"))
+        ])
+        .expect("update must connect");
+    assert_eq!(changeset, [(2, Some(h!("B'"))), (3, Some(h!("C")))].into());
+    assert!(prev_tip.is_tip_of(&exp_chain));
+
+    let revert_changeset = chain.set_tip(prev_tip).expect("prev tip has genesis");
+    assert_eq!(revert_changeset, [(2, Some(h!("B"))), (3, None)].into());
+    assert_eq!(chain, exp_chain);
+
+    // a tip without genesis is rejected and leaves the chain unchanged
+    assert_eq!(
+        chain.set_tip(CheckPoint::new(block_id!(5, "E"))),
+        Err(ApplyChangeSetError::MissingGenesis(MissingGenesisError))
+    );
+    assert_eq!(chain, exp_chain);
+
+    // so is a tip with a different genesis
+    assert_eq!(
+        chain.set_tip(CheckPoint::new(block_id!(0, "_'"))),
+        Err(ApplyChangeSetError::GenesisAltered {
+            expected: h!("_"),
+            found: h!("_'"),
+        })
+    );
+    assert_eq!(chain, exp_chain);
+}
+
+#[test]
+fn local_chain_contains_blocks() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+
+    assert!(chain.contains_block(block_id!(1, "A")));
+    assert!(!chain.contains_block(block_id!(1, "A'")));
+    assert!(!chain.contains_block(block_id!(2, "B")));
+
+    assert!(chain.contains_any([block_id!(2, "B"), block_id!(3, "C")]));
+    assert!(!chain.contains_any([block_id!(2, "B"), block_id!(3, "C'")]));
+    assert!(!chain.contains_any([]));
+
+    assert!(chain.contains_all([block_id!(1, "A"), block_id!(3, "C")]));
+    assert!(!chain.contains_all([block_id!(1, "A"), block_id!(2, "B")]));
+    assert!(chain.contains_all([]));
+}
+
+#[test]
+fn local_chain_apply_changeset() {
+    struct TestCase<'a> {
+        name: &'static str,
+        original: LocalChain,
+        changeset: &'a [(u32, Option<BlockHash>)],
+        exp_result: Result<(), ApplyChangeSetError>,
+        exp_final: LocalChain,
+    }
+
+    let test_cases = [
+        TestCase {
+            name: "remove_tip",
+            original: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+            changeset: &[(2, None)],
+            exp_result: Ok(()),
+            exp_final: local_chain![(0, h!("_")), (1, h!("A"))],
+        },
+        TestCase {
+            name: "remove_and_replace_tip",
+            original: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+            changeset: &[(1, None), (2, Some(h!("B'")))],
+            exp_result: Ok(()),
+            exp_final: local_chain![(0, h!("_")), (2, h!("B'"))],
+        },
+        TestCase {
+            name: "reorg_to_longer_chain",
+            original: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))],
+            changeset: &[(2, Some(h!("B'"))), (3, None), (4, Some(h!("D'")))],
+            exp_result: Ok(()),
+            exp_final: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D'"))],
+        },
+        TestCase {
+            name: "remove_block_in_the_middle_and_extend",
+            original: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+            changeset: &[(1, None), (3, Some(h!("C")))],
+            exp_result: Ok(()),
+            exp_final: local_chain![(0, h!("_")), (2, h!("B")), (3, h!("C"))],
+        },
+        TestCase {
+            name: "remove_block_in_the_middle",
+            original: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+            changeset: &[(1, None)],
+            exp_result: Ok(()),
+            exp_final: local_chain![(0, h!("_")), (2, h!("B"))],
+        },
+        TestCase {
+            name: "remove_missing_block_in_the_middle",
+            original: local_chain![(0, h!("_")), (2, h!("B"))],
+            changeset: &[(1, None)],
+            exp_result: Ok(()),
+            exp_final: local_chain![(0, h!("_")), (2, h!("B"))],
+        },
+        TestCase {
+            name: "remove_genesis",
+            original: local_chain![(0, h!("_"))],
+            changeset: &[(0, None)],
+            exp_result: Err(ApplyChangeSetError::MissingGenesis(MissingGenesisError)),
+            exp_final: local_chain![(0, h!("_"))],
+        },
+        TestCase {
+            name: "alter_genesis",
+            original: local_chain![(0, h!("_")), (1, h!("A"))],
+            changeset: &[(0, Some(h!("_'"))), (1, None)],
+            exp_result: Err(ApplyChangeSetError::GenesisAltered {
+                expected: h!("_"),
+                found: h!("_'"),
+            }),
+            exp_final: local_chain![(0, h!("_")), (1, h!("A"))],
+        },
+        TestCase {
+            name: "full_reorg_keeping_genesis",
+            original: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+            changeset: &[
+                (0, Some(h!("_"))),
+                (1, Some(h!("A'"))),
+                (2, None),
+                (3, Some(h!("C'"))),
+            ],
+            exp_result: Ok(()),
+            exp_final: local_chain![(0, h!("_")), (1, h!("A'")), (3, h!("C'"))],
+        },
+    ];
+
+    for (i, t) in test_cases.into_iter().enumerate() {
+        println!("running test case {}: '{}'", i, t.name);
+        let mut chain = t.original;
+        let changeset = t.changeset.iter().cloned().collect::<ChangeSet>();
+        assert_eq!(
+            chain.apply_changeset(&changeset),
+            t.exp_result,
+            "[{}:{}] unexpected result",
+            i,
+            t.name
+        );
+        assert_eq!(
+            chain, t.exp_final,
+            "[{}:{}] unexpected final chain",
+            i, t.name
+        );
+    }
+}
+
+#[test]
+fn checkpoint_iter_with_prev() {
+    let tip = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))].tip();
+    let pairs = tip
+        .iter_with_prev()
+        .map(|(cp, prev)| (cp.block_id(), prev.map(|prev| prev.block_id())))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        pairs,
+        vec![
+            (block_id!(3, "C"), Some(block_id!(1, "A"))),
+            (block_id!(1, "A"), Some(block_id!(0, "_"))),
+            (block_id!(0, "_"), None),
+        ]
+    );
+
+    // every checkpoint is linked to a lower checkpoint
+    assert!(tip
+        .iter_with_prev()
+        .all(|(cp, prev)| prev.map_or(true, |prev| prev.height() < cp.height())));
+
+    let genesis = CheckPoint::new(block_id!(0, "_"));
+    assert_eq!(genesis.iter_with_prev().count(), 1);
+}
+
+#[test]
+fn local_chain_would_introduce_older_blocks() {
+    let chain = local_chain![(0, h!("_")), (2, h!("B")), (3, h!("C"))];
+
+    // extends the tip
+    let update = chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C")), (4, h!("D"))];
+    assert!(!chain.would_introduce_older_blocks(&update.tip));
+
+    // introduces a block below the point of agreement
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (3, h!("C")), (4, h!("D"))];
+    assert!(chain.would_introduce_older_blocks(&update.tip));
+
+    // introduces a block above the point of agreement only
+    let update = chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C'"))];
+    assert!(!chain.would_introduce_older_blocks(&update.tip));
+}
+
+#[test]
+fn block_id_with_height_and_succeeds() {
+    let block = block_id!(1, "A");
+    let moved = block.with_height(2);
+    assert_eq!(moved.height, 2);
+    assert_eq!(moved.hash, block.hash);
+
+    assert!(moved.succeeds(&block));
+    assert!(!block.succeeds(&moved));
+    assert!(!block.succeeds(&block));
+    assert!(!block.with_height(3).succeeds(&block));
+
+    // the height of `other` does not overflow at the maximum height
+    let max = block.with_height(u32::MAX);
+    assert!(max.succeeds(&block.with_height(u32::MAX - 1)));
+    assert!(!block.with_height(0).succeeds(&max));
+}
+
+#[test]
+fn local_chain_shrink_to_recent() {
+    let chain = local_chain![
+        (0, h!("_")),
+        (1, h!("A")),
+        (2, h!("B")),
+        (3, h!("C")),
+        (5, h!("E"))
+    ];
+
+    let mut shrunk = chain.clone();
+    assert_eq!(shrunk.shrink_to_recent(2), [(1, None), (2, None)].into());
+    assert_eq!(
+        shrunk,
+        local_chain![(0, h!("_")), (3, h!("C")), (5, h!("E"))]
+    );
+    assert_eq!(
+        shrunk.is_block_in_chain(block_id!(1, "A"), shrunk.tip().block_id()),
+        Ok(None)
+    );
+
+    let mut shrunk = chain.clone();
+    assert_eq!(
+        shrunk.shrink_to_recent(0),
+        [(1, None), (2, None), (3, None), (5, None)].into()
+    );
+    assert_eq!(shrunk, local_chain![(0, h!("_"))]);
+
+    let mut shrunk = chain.clone();
+    assert_eq!(shrunk.shrink_to_recent(10), ChangeSet::default());
+    assert_eq!(shrunk, chain);
+
+    // the changeset of a shrink replays onto the original chain
+    let mut shrunk = chain.clone();
+    let changeset = shrunk.shrink_to_recent(3);
+    let mut replica = chain.clone();
+    assert_eq!(replica.apply_changeset(&changeset), Ok(()));
+    assert_eq!(replica, shrunk);
+}
+
+#[test]
+fn local_chain_apply_update_with() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D"))];
+
+    let mut events = Vec::new();
+    let changeset = chain
+        .apply_update_with(update, |event| events.push(event))
+        .expect("update must connect");
+
+    assert_eq!(
+        changeset,
+        [(2, Some(h!("B'"))), (3, None), (4, Some(h!("D")))].into()
+    );
+    assert_eq!(
+        events,
+        vec![
+            ChainEvent::Removed(block_id!(2, "B")),
+            ChainEvent::Added(block_id!(2, "B'")),
+            ChainEvent::Removed(block_id!(3, "C")),
+            ChainEvent::Added(block_id!(4, "D")),
+        ]
+    );
+
+    // an update that cannot connect emits no events
+    let mut events = Vec::new();
+    let update = chain_update![(0, h!("_")), (2, h!("B")), (5, h!("E"))];
+    assert!(chain
+        .apply_update_with(update, |event| events.push(event))
+        .is_err());
+    assert!(events.is_empty());
+
+    // neither does an update which connects but is refused when it is applied, even though it
+    // would remove blocks below the refused block
+    chain.set_trusted_checkpoints([(4, h!("D"))].into());
+    let mut events = Vec::new();
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B''")), (4, h!("D'"))];
+    assert_eq!(
+        chain.apply_update_with(update, |event| events.push(event)),
+        Err(CannotConnectError {
+            try_include_height: 4
+        })
+    );
+    assert!(events.is_empty());
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D"))]
+    );
+}
+
+#[test]
+fn local_chain_block_at_or_below() {
+    let chain = local_chain![(0, h!("_")), (2, h!("B")), (5, h!("E"))];
+    assert_eq!(chain.block_at_or_below(0), block_id!(0, "_"));
+    assert_eq!(chain.block_at_or_below(1), block_id!(0, "_"));
+    assert_eq!(chain.block_at_or_below(2), block_id!(2, "B"));
+    assert_eq!(chain.block_at_or_below(4), block_id!(2, "B"));
+    assert_eq!(chain.block_at_or_below(100), block_id!(5, "E"));
+}
+
+#[test]
+fn local_chain_heights_to_fetch() {
+    let chain = local_chain![(0, h!("_")), (2, h!("B")), (3, h!("C")), (5, h!("E"))];
+    assert_eq!(chain.heights_to_fetch(0, 6), vec![1, 4, 6]);
+    assert_eq!(chain.heights_to_fetch(2, 3), Vec::<u32>::new());
+    assert_eq!(chain.heights_to_fetch(4, 4), vec![4]);
+    assert_eq!(chain.heights_to_fetch(7, 6), Vec::<u32>::new());
+}
+
+#[test]
+fn update_from_header() {
+    let genesis = block_id!(0, "_");
+    let header = Header {
+        version: bitcoin::block::Version::default(),
+        prev_blockhash: genesis.hash,
+        merkle_root: bitcoin::hash_types::TxMerkleNode::all_zeros(),
+        time: 0,
+        bits: bitcoin::CompactTarget::default(),
+        nonce: 0,
+    };
+    let header_block = BlockId {
+        height: 1,
+        hash: header.block_hash(),
+    };
+
+    let update = Update::from_header(&header, 1);
+    assert!(!update.introduce_older_blocks);
+    assert_eq!(
+        update
+            .tip
+            .iter()
+            .map(|cp| cp.block_id())
+            .collect::<Vec<_>>(),
+        vec![header_block, genesis]
+    );
+
+    let mut chain = local_chain![(0, genesis.hash)];
+    assert_eq!(
+        chain.apply_update(update),
+        Ok([(1, Some(header_block.hash))].into())
+    );
+    assert_eq!(chain.tip().block_id(), header_block);
+}
+
+#[test]
+fn local_chain_descendants_invalidated_by() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C")), (4, h!("D"))];
+
+    for block_id in [block_id!(3, "C"), block_id!(2, "B"), block_id!(3, "C'")] {
+        let invalidated = chain.descendants_invalidated_by(block_id);
+        let mut disconnected = chain.clone();
+        let changeset = disconnected
+            .disconnect_from(block_id)
+            .expect("must not disconnect genesis");
+        assert_eq!(
+            invalidated.iter().map(|b| b.height).collect::<Vec<_>>(),
+            changeset.keys().copied().collect::<Vec<_>>(),
+            "preview must match the effect of disconnect_from for {:?}",
+            block_id
+        );
+    }
+
+    assert_eq!(
+        chain.descendants_invalidated_by(block_id!(3, "C")),
+        vec![block_id!(3, "C"), block_id!(4, "D")]
+    );
+}
+
+#[test]
+fn checkpoint_pairs_roundtrip() {
+    let pairs = vec![(0, h!("_")), (2, h!("B")), (5, h!("E"))];
+    let cp = CheckPoint::from_pairs(pairs.clone()).expect("pairs are in ascending order");
+    assert_eq!(cp.block_id(), block_id!(5, "E"));
+    assert_eq!(cp.to_pairs(), pairs);
+
+    assert!(matches!(CheckPoint::from_pairs(vec![]), Err(None)));
+    assert!(matches!(
+        CheckPoint::from_pairs(vec![(0, h!("_")), (2, h!("B")), (2, h!("B'"))]),
+        Err(Some(cp)) if cp.block_id() == block_id!(2, "B")
+    ));
+}
+
+#[test]
+fn local_chain_apply_update_checked() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A"))];
+
+    let update = chain_update![(0, h!("other_genesis")), (1, h!("A")), (2, h!("B"))];
+    assert_eq!(
+        chain.apply_update_checked(update),
+        Err(ApplyUpdateError::GenesisMismatch {
+            expected: h!("_"),
+            found: h!("other_genesis"),
+        })
+    );
+    assert_eq!(chain, local_chain![(0, h!("_")), (1, h!("A"))]);
+
+    let update = chain_update![(0, h!("_")), (2, h!("B"))];
+    assert_eq!(
+        chain.apply_update_checked(update),
+        Err(ApplyUpdateError::CannotConnect(CannotConnectError {
+            try_include_height: 1
+        }))
+    );
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    assert_eq!(
+        chain.apply_update_checked(update),
+        Ok([(2, Some(h!("B")))].into())
+    );
+
+    // updates without a genesis block are not checked for a genesis mismatch
+    let update = CheckPoint::from_block_ids([block_id!(2, "B"), block_id!(3, "C")])
+        .expect("must be in order")
+        .into_update(false);
+    assert_eq!(
+        chain.apply_update_checked(update),
+        Ok([(3, Some(h!("C")))].into())
+    );
+}
+
+#[test]
+fn local_chain_tip_changed_since() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+    let last_seen_tip = chain.tip();
+    assert_eq!(chain.tip_changed_since(&last_seen_tip), None);
+
+    // an equivalent checkpoint list that is not the same `Arc` is also unchanged
+    let equivalent_tip = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))].tip();
+    assert_eq!(chain.tip_changed_since(&equivalent_tip), None);
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D"))];
+    chain.apply_update(update).expect("update must connect");
+    assert_eq!(
+        chain.tip_changed_since(&last_seen_tip),
+        Some(TipChange {
+            added: vec![block_id!(2, "B'"), block_id!(4, "D")],
+            removed: vec![block_id!(2, "B"), block_id!(3, "C")],
+        })
+    );
+
+    let last_seen_tip = chain.tip();
+    chain
+        .insert_block(block_id!(5, "E"))
+        .expect("must insert block");
+    assert_eq!(
+        chain.tip_changed_since(&last_seen_tip),
+        Some(TipChange {
+            added: vec![block_id!(5, "E")],
+            removed: vec![],
+        })
+    );
+}
+
+#[test]
+fn local_chain_apply_update_if_tip() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A"))];
+    let expected_tip = chain.tip().block_id();
+
+    // another worker extends the chain first
+    let mut other_worker_chain = chain.clone();
+    other_worker_chain
+        .apply_update(chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))])
+        .expect("update must connect");
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))];
+    assert_eq!(
+        other_worker_chain.apply_update_if_tip(update.clone(), expected_tip),
+        Err(ApplyUpdateError::TipMoved {
+            expected: expected_tip,
+            found: block_id!(2, "B"),
+        })
+    );
+    assert_eq!(
+        other_worker_chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))]
+    );
+
+    assert_eq!(
+        chain.apply_update_if_tip(update, expected_tip),
+        Ok([(2, Some(h!("B'")))].into())
+    );
+}
+
+#[test]
+fn local_chain_apply_header_reports_reorg() {
+    fn header_from_prev_blockhash(prev_blockhash: BlockHash) -> Header {
+        Header {
+            version: bitcoin::block::Version::default(),
+            prev_blockhash,
+            merkle_root: bitcoin::hash_types::TxMerkleNode::all_zeros(),
+            time: 0,
+            bits: bitcoin::CompactTarget::default(),
+            nonce: 0,
+        }
+    }
+
+    // a header which extends the tip is not a reorg
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A"))];
+    let header = header_from_prev_blockhash(h!("A"));
+    assert_eq!(chain.apply_header_reports_reorg(&header, 2), Ok(false));
+    assert_eq!(chain.tip().block_id(), (2, header.block_hash()).into());
+
+    // applying the same header again is not a reorg
+    assert_eq!(chain.apply_header_reports_reorg(&header, 2), Ok(false));
+
+    // a header which replaces blocks is a reorg
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+    let header = header_from_prev_blockhash(h!("A"));
+    assert_eq!(chain.apply_header_reports_reorg(&header, 2), Ok(true));
+    assert_eq!(chain.tip().block_id(), (2, header.block_hash()).into());
+}
+
+#[test]
+fn estimated_heap_bytes() {
+    let genesis = CheckPoint::new(block_id!(0, "_"));
+    let node_bytes = genesis.estimated_heap_bytes();
+    assert!(node_bytes > 0);
+
+    // the estimate grows linearly with the number of checkpoints
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    assert_eq!(chain.tip().estimated_heap_bytes(), 3 * node_bytes);
+
+    // the chain estimate also covers the index entries
+    let single_chain = local_chain![(0, h!("_"))];
+    let chain_bytes = single_chain.estimated_heap_bytes();
+    assert!(chain_bytes > node_bytes);
+    assert_eq!(chain.estimated_heap_bytes(), 3 * chain_bytes);
+}
+
+#[test]
+fn local_chain_replace_subchain() {
+    struct TestCase {
+        name: &'static str,
+        fork_point: BlockId,
+        new_blocks: Vec<BlockId>,
+        exp_result: Result<ChangeSet, ReplaceError>,
+        exp_chain: LocalChain,
+    }
+
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+
+    let test_cases = [
+        TestCase {
+            name: "replace_above_fork_point",
+            fork_point: block_id!(1, "A"),
+            new_blocks: vec![block_id!(2, "B'"), block_id!(4, "D'")],
+            exp_result: Ok([(2, Some(h!("B'"))), (3, None), (4, Some(h!("D'")))].into()),
+            exp_chain: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D'"))],
+        },
+        TestCase {
+            name: "no_new_blocks_removes_above_fork_point",
+            fork_point: block_id!(1, "A"),
+            new_blocks: vec![],
+            exp_result: Ok([(2, None), (3, None)].into()),
+            exp_chain: local_chain![(0, h!("_")), (1, h!("A"))],
+        },
+        TestCase {
+            name: "fork_point_not_in_chain",
+            fork_point: block_id!(1, "A'"),
+            new_blocks: vec![block_id!(2, "B'")],
+            exp_result: Err(ReplaceError::MissingForkPoint(block_id!(1, "A'"))),
+            exp_chain: chain.clone(),
+        },
+        TestCase {
+            name: "new_block_not_above_fork_point",
+            fork_point: block_id!(2, "B"),
+            new_blocks: vec![block_id!(2, "B'")],
+            exp_result: Err(ReplaceError::InvalidOrder { height: 2 }),
+            exp_chain: chain.clone(),
+        },
+        TestCase {
+            name: "new_blocks_not_ascending",
+            fork_point: block_id!(1, "A"),
+            new_blocks: vec![block_id!(3, "C'"), block_id!(2, "B'")],
+            exp_result: Err(ReplaceError::InvalidOrder { height: 2 }),
+            exp_chain: chain.clone(),
+        },
+    ];
+
+    for (i, t) in test_cases.into_iter().enumerate() {
+        println!("[{}] running test case: {}", i, t.name);
+
+        let mut chain = chain.clone();
+        let result = chain.replace_subchain(t.fork_point, t.new_blocks);
+        assert_eq!(result, t.exp_result, "[{}:{}] unexpected result", i, t.name);
+        assert_eq!(chain, t.exp_chain, "[{}:{}] unexpected chain", i, t.name);
+    }
+}
+
+#[test]
+fn local_chain_contiguous_suffix() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    assert_eq!(chain.contiguous_suffix(), 0..=2);
+
+    let chain = local_chain![
+        (0, h!("_")),
+        (2, h!("B")),
+        (4, h!("D")),
+        (5, h!("E")),
+        (6, h!("F"))
+    ];
+    assert_eq!(chain.contiguous_suffix(), 4..=6);
+
+    let chain = local_chain![(0, h!("_")), (2, h!("B"))];
+    assert_eq!(chain.contiguous_suffix(), 2..=2);
+
+    let chain = local_chain![(0, h!("_"))];
+    assert_eq!(chain.contiguous_suffix(), 0..=0);
+}
+
+#[test]
+fn local_chain_invert_changeset() {
+    let original = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+
+    let mut chain = original.clone();
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D"))];
+    let changeset =
+        compute_merge_changeset(&chain.tip(), &update.tip, true).expect("update must connect");
+
+    let inverse = chain.invert_changeset(&changeset);
+    assert_eq!(
+        inverse,
+        [(2, Some(h!("B"))), (3, Some(h!("C"))), (4, None)].into()
+    );
+
+    chain
+        .apply_changeset(&changeset)
+        .expect("must apply changeset");
+    assert_ne!(chain, original);
+    chain.apply_changeset(&inverse).expect("must apply inverse");
+    assert_eq!(chain, original);
+}
+
+#[test]
+fn local_chain_apply_updates() {
+    let original = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    let updates = vec![
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))],
+        chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C'")), (4, h!("D"))],
+        chain_update![(0, h!("_")), (4, h!("D")), (5, h!("E"))],
+    ];
+
+    let mut exp_chain = original.clone();
+    for update in updates.clone() {
+        exp_chain.apply_update(update).expect("update must connect");
+    }
+
+    let mut chain = original.clone();
+    let changeset = chain.apply_updates(updates).expect("updates must connect");
+    assert_eq!(chain, exp_chain);
+    assert_eq!(
+        changeset,
+        [(3, Some(h!("C'"))), (4, Some(h!("D"))), (5, Some(h!("E")))].into()
+    );
+
+    // changes that are reverted by later updates are not included
+    let mut chain = original.clone();
+    let updates = vec![
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))],
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+    ];
+    assert_eq!(chain.apply_updates(updates), Ok(ChangeSet::default()));
+    assert_eq!(chain, original);
+
+    // an update that cannot connect leaves the chain unchanged
+    let mut chain = original.clone();
+    let updates = vec![
+        chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C"))],
+        chain_update![(0, h!("_")), (4, h!("D"))],
+    ];
+    assert_eq!(
+        chain.apply_updates(updates),
+        Err(CannotConnectError {
+            try_include_height: 1
+        })
+    );
+    assert_eq!(chain, original);
+}
+
+#[test]
+fn local_chain_apply_updates_refuses_altered_genesis() {
+    let original = local_chain![(0, h!("_")), (1, h!("A"))];
+    let mut chain = original.clone();
+    let updates = vec![
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+        chain_update![(0, h!("_'")), (2, h!("B")), (3, h!("C"))],
+    ];
+    assert_eq!(
+        chain.apply_updates(updates),
+        Err(CannotConnectError {
+            try_include_height: 0
+        })
+    );
+    assert_eq!(chain, original);
+}
+
+#[test]
+fn local_chain_apply_updates_trusted_checkpoints() {
+    let mut original = local_chain![(0, h!("_")), (1, h!("A"))];
+    original.set_trusted_checkpoints([(3, h!("C"))].into());
+
+    let mut chain = original.clone();
+    let updates = vec![
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+        chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C'"))],
+    ];
+    assert_eq!(
+        chain.apply_updates(updates),
+        Err(CannotConnectError {
+            try_include_height: 3
+        })
+    );
+    assert_eq!(chain, original);
+
+    let updates = vec![
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+        chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C"))],
+    ];
+    assert_eq!(
+        chain.apply_updates(updates),
+        Ok([(2, Some(h!("B"))), (3, Some(h!("C")))].into())
+    );
+}
+
+#[test]
+fn local_chain_apply_updates_checks_each_update() {
+    let original = local_chain![(0, h!("_")), (1, h!("A"))];
+
+    // an update which alters the genesis block is refused, even if a later update reverts it
+    let mut chain = original.clone();
+    let updates = vec![
+        chain_update![(0, h!("_'")), (1, h!("A"))],
+        chain_update![(0, h!("_")), (1, h!("A"))],
+    ];
+    assert_eq!(
+        chain.apply_updates(updates),
+        Err(CannotConnectError {
+            try_include_height: 0
+        })
+    );
+    assert_eq!(chain, original);
+
+    // as is an update which contradicts a trusted checkpoint
+    let mut chain = original.clone();
+    chain.set_trusted_checkpoints([(2, h!("B"))].into());
+    let updates = vec![
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))],
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+    ];
+    assert_eq!(
+        chain.apply_update(updates[0].clone()),
+        Err(CannotConnectError {
+            try_include_height: 2
+        })
+    );
+    assert_eq!(
+        chain.apply_updates(updates),
+        Err(CannotConnectError {
+            try_include_height: 2
+        })
+    );
+    assert_eq!(chain, original);
+}
+
+#[test]
+fn checkpoint_walk_while() {
+    let tip = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))].tip();
+    let heights = |cps: Vec<CheckPoint>| cps.iter().map(CheckPoint::height).collect::<Vec<_>>();
+
+    // the first checkpoint for which the predicate is false is not yielded
+    assert_eq!(
+        heights(tip.walk_while(|cp| cp.height() > 1).collect()),
+        [3, 2]
+    );
+    assert_eq!(heights(tip.walk_while(|_| true).collect()), [3, 2, 1, 0]);
+    assert_eq!(
+        heights(tip.walk_while(|_| false).collect()),
+        Vec::<u32>::new()
+    );
+
+    // iteration stops at the first false, even if the predicate would be true again below it
+    assert_eq!(
+        heights(tip.walk_while(|cp| cp.height() != 2).collect()),
+        [3]
+    );
+
+    // the predicate is not called after it returns false
+    let mut calls = 0;
+    let _ = tip
+        .walk_while(|cp| {
+            calls += 1;
+            cp.height() > 2
+        })
+        .count();
+    assert_eq!(calls, 2);
+}
+
+#[test]
+fn local_chain_encode_decode() {
+    let chain = local_chain![
+        (0, h!("_")),
+        (1, h!("A")),
+        (200, h!("B")),
+        (u32::MAX, h!("C"))
+    ];
+    let bytes = chain.encode();
+    // heights take 1, 1, 2 and 5 bytes
+    assert_eq!(bytes.len(), 4 * 32 + 9);
+    assert_eq!(LocalChain::decode(&bytes), Ok(chain.clone()));
+
+    assert_eq!(
+        LocalChain::decode(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::Truncated)
+    );
+    assert_eq!(LocalChain::decode(&[0x80]), Err(DecodeError::Truncated));
+    assert_eq!(
+        LocalChain::decode(&[0xff, 0xff, 0xff, 0xff, 0x1f]),
+        Err(DecodeError::InvalidHeight)
+    );
+
+    // heights must be minimally encoded, so that a chain has exactly one encoding
+    let genesis = local_chain![(0, h!("_"))].encode();
+    for overlong_height in [&[0x80, 0x00][..], &[0x80, 0x80, 0x00]] {
+        let mut overlong = overlong_height.to_vec();
+        overlong.extend_from_slice(&genesis[1..]);
+        assert_eq!(
+            LocalChain::decode(&overlong),
+            Err(DecodeError::NonCanonicalHeight)
+        );
+    }
+    assert_eq!(
+        LocalChain::decode(&[]),
+        Err(DecodeError::MissingGenesis(MissingGenesisError))
+    );
+
+    let mut not_ascending = local_chain![(0, h!("_")), (2, h!("B"))].encode();
+    not_ascending.extend(local_chain![(0, h!("_")), (1, h!("A"))].encode()[33..].iter());
+    assert_eq!(
+        LocalChain::decode(&not_ascending),
+        Err(DecodeError::NotAscending { height: 1 })
+    );
+}
+
+#[test]
+fn local_chain_is_applied() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (3, h!("C"))];
+
+    let changeset =
+        compute_merge_changeset(&chain.tip(), &update.tip, true).expect("update must connect");
+    assert!(!chain.is_applied(&changeset));
+    assert_eq!(chain.apply_update(update.clone()), Ok(changeset.clone()));
+    assert!(chain.is_applied(&changeset));
+
+    // applying the same update again is a no-op
+    assert_eq!(chain.apply_update(update), Ok(ChangeSet::default()));
+    assert!(chain.is_applied(&changeset));
+    assert!(chain.is_applied(&ChangeSet::default()));
+    assert!(chain.is_applied(&[(4, None)].into()));
+}
+
+#[test]
+fn local_chain_replace_block() {
+    struct TestCase {
+        name: &'static str,
+        block_id: BlockId,
+        exp_changeset: ChangeSet,
+        exp_chain: LocalChain,
+    }
+
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+
+    let test_cases = [
+        TestCase {
+            name: "replace_block_in_the_middle",
+            block_id: block_id!(2, "B'"),
+            exp_changeset: [(2, Some(h!("B'"))), (3, None)].into(),
+            exp_chain: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B'"))],
+        },
+        TestCase {
+            name: "replace_tip",
+            block_id: block_id!(3, "C'"),
+            exp_changeset: [(3, Some(h!("C'")))].into(),
+            exp_chain: local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C'"))],
+        },
+        TestCase {
+            name: "same_block_removes_blocks_above",
+            block_id: block_id!(1, "A"),
+            exp_changeset: [(2, None), (3, None)].into(),
+            exp_chain: local_chain![(0, h!("_")), (1, h!("A"))],
+        },
+        TestCase {
+            name: "insert_above_tip",
+            block_id: block_id!(5, "E"),
+            exp_changeset: [(5, Some(h!("E")))].into(),
+            exp_chain: local_chain![
+                (0, h!("_")),
+                (1, h!("A")),
+                (2, h!("B")),
+                (3, h!("C")),
+                (5, h!("E"))
+            ],
+        },
+        TestCase {
+            name: "same_genesis_removes_blocks_above",
+            block_id: block_id!(0, "_"),
+            exp_changeset: [(1, None), (2, None), (3, None)].into(),
+            exp_chain: local_chain![(0, h!("_"))],
+        },
+        TestCase {
+            name: "insert_at_max_height",
+            block_id: block_id!(u32::MAX, "Z"),
+            exp_changeset: [(u32::MAX, Some(h!("Z")))].into(),
+            exp_chain: local_chain![
+                (0, h!("_")),
+                (1, h!("A")),
+                (2, h!("B")),
+                (3, h!("C")),
+                (u32::MAX, h!("Z"))
+            ],
+        },
+    ];
+
+    for (i, t) in test_cases.into_iter().enumerate() {
+        println!("[{}] running test case: {}", i, t.name);
+
+        let mut chain = chain.clone();
+        assert_eq!(
+            chain.replace_block(t.block_id),
+            Ok(t.exp_changeset),
+            "[{}:{}] unexpected changeset",
+            i,
+            t.name
+        );
+        assert_eq!(chain, t.exp_chain, "[{}:{}] unexpected chain", i, t.name);
+    }
+
+    // the genesis block cannot be replaced
+    let mut replaced = chain.clone();
+    assert_eq!(
+        replaced.replace_block(block_id!(0, "_'")),
+        Err(AlterCheckPointError {
+            height: 0,
+            original_hash: h!("_"),
+            update_hash: Some(h!("_'")),
+        })
+    );
+    assert_eq!(replaced, chain);
+}
+
+#[test]
+fn checkpoint_hash_at() {
+    let cp = local_chain![(0, h!("_")), (2, h!("B")), (3, h!("C"))].tip();
+    assert_eq!(cp.hash_at(0), Some(h!("_")));
+    assert_eq!(cp.hash_at(1), None);
+    assert_eq!(cp.hash_at(2), Some(h!("B")));
+    assert_eq!(cp.hash_at(3), Some(h!("C")));
+    assert_eq!(cp.hash_at(4), None);
+}
+
+#[test]
+fn local_chain_stats() {
+    let mut chain = LocalChain::from_changeset(
+        [(0, Some(h!("_"))), (1, Some(h!("A"))), (2, Some(h!("B")))].into(),
+    )
+    .expect("must have genesis");
+    assert_eq!(chain.stats(), ChainStats::default());
+
+    chain
+        .apply_update(chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C"))])
+        .expect("update must connect");
+    assert_eq!(
+        chain.stats(),
+        ChainStats {
+            blocks_added: 1,
+            blocks_removed: 0,
+            reorgs: 0,
+        }
+    );
+
+    // replace block 2 and remove block 3
+    chain
+        .apply_update(chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))])
+        .expect("update must connect");
+    assert_eq!(
+        chain.stats(),
+        ChainStats {
+            blocks_added: 2,
+            blocks_removed: 2,
+            reorgs: 1,
+        }
+    );
+
+    chain
+        .disconnect_from(block_id!(2, "B'"))
+        .expect("must not disconnect genesis");
+    assert_eq!(
+        chain.stats(),
+        ChainStats {
+            blocks_added: 2,
+            blocks_removed: 3,
+            reorgs: 2,
+        }
+    );
+
+    // stats are not part of the chain's equality
+    assert_eq!(chain, local_chain![(0, h!("_")), (1, h!("A"))]);
+}
+
+#[test]
+fn local_chain_filter_canonical() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    let claims = [
+        block_id!(3, "C"),
+        block_id!(1, "A'"),
+        block_id!(2, "B"),
+        block_id!(1, "A"),
+    ];
+    assert_eq!(
+        filter_canonical(&chain, chain.tip().block_id(), claims),
+        Ok(vec![block_id!(3, "C"), block_id!(1, "A")])
+    );
+    // blocks above the given tip are not canonical
+    assert_eq!(
+        filter_canonical(&chain, block_id!(1, "A"), claims),
+        Ok(vec![block_id!(1, "A")])
+    );
+}
+
+#[test]
+fn local_chain_iter_blocks() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    let ascending = vec![block_id!(0, "_"), block_id!(1, "A"), block_id!(3, "C")];
+    assert_eq!(chain.iter_blocks().collect::<Vec<_>>(), ascending);
+
+    // iterating in reverse is the same as iterating the checkpoints
+    assert_eq!(
+        chain.iter_blocks().rev().collect::<Vec<_>>(),
+        chain
+            .tip()
+            .iter()
+            .map(|cp| cp.block_id())
+            .collect::<Vec<_>>()
+    );
+
+    // consume from both ends
+    let mut blocks = chain.iter_blocks();
+    assert_eq!(blocks.next(), Some(block_id!(0, "_")));
+    assert_eq!(blocks.next_back(), Some(block_id!(3, "C")));
+    assert_eq!(blocks.next_back(), Some(block_id!(1, "A")));
+    assert_eq!(blocks.next(), None);
+}
+
+#[test]
+fn local_chain_new_from_genesis_and_tip() {
+    let chain = LocalChain::new_from_genesis(h!("_"));
+    assert_eq!(chain, LocalChain::from_genesis_hash(h!("_")).0);
+    assert_eq!(chain.tip().block_id(), block_id!(0, "_"));
+
+    let tip = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))].tip();
+    assert_eq!(
+        LocalChain::new_from_tip(tip.clone()),
+        LocalChain::from_tip(tip)
+    );
+
+    let tip = CheckPoint::new(block_id!(1, "A"));
+    assert_eq!(LocalChain::new_from_tip(tip), Err(MissingGenesisError));
+}
+
+#[test]
+fn local_chain_integrity_report() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    let report = chain.integrity_report();
+    assert_eq!(
+        report,
+        IntegrityReport {
+            index_matches_tip: true,
+            genesis_present: true,
+            contiguous: true,
+        }
+    );
+    assert!(report.is_ok());
+
+    let chain = local_chain![(0, h!("_")), (2, h!("B"))];
+    let report = chain.integrity_report();
+    assert!(!report.contiguous);
+    assert!(report.is_ok());
+}
+
+#[test]
+fn checkpoint_prev_n() {
+    let cp = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))].tip();
+    assert_eq!(
+        cp.prev_n(0).map(|cp| cp.block_id()),
+        Some(block_id!(3, "C"))
+    );
+    assert_eq!(
+        cp.prev_n(1).map(|cp| cp.block_id()),
+        Some(block_id!(1, "A"))
+    );
+    assert_eq!(
+        cp.prev_n(2).map(|cp| cp.block_id()),
+        Some(block_id!(0, "_"))
+    );
+    assert_eq!(cp.prev_n(3).map(|cp| cp.block_id()), None);
+}
+
+#[test]
+fn local_chain_preview_update() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (3, h!("C"))];
+    let preview_tip = chain.preview_update(&update).expect("update must connect");
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))]
+    );
+
+    let mut applied = chain.clone();
+    applied.apply_update(update).expect("update must connect");
+    assert!(preview_tip.is_tip_of(&applied));
+
+    let update = chain_update![(0, h!("_")), (3, h!("C"))];
+    assert_eq!(
+        chain.preview_update(&update).map(|tip| tip.block_id()),
+        Err(CannotConnectError {
+            try_include_height: 1
+        })
+    );
+}
+
+#[test]
+fn local_chain_block_ids_from() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C")), (4, h!("D"))];
+    let block_ids_from = |start| chain.block_ids_from(start).collect::<Vec<_>>();
+
+    // `start` is inclusive, and heights without a checkpoint are skipped
+    assert_eq!(
+        block_ids_from(1),
+        vec![block_id!(1, "A"), block_id!(3, "C"), block_id!(4, "D")]
+    );
+    assert_eq!(
+        block_ids_from(2),
+        vec![block_id!(3, "C"), block_id!(4, "D")]
+    );
+    assert_eq!(block_ids_from(0), chain.iter_blocks().collect::<Vec<_>>());
+    // the tip is the last block yielded
+    assert_eq!(block_ids_from(4), vec![block_id!(4, "D")]);
+    assert_eq!(block_ids_from(5), vec![]);
+    assert_eq!(block_ids_from(u32::MAX), vec![]);
+}
+
+#[test]
+fn local_chain_from_blocks_sorted() {
+    let blocks = vec![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    let chain = LocalChain::from_blocks_sorted(blocks.clone()).expect("must have genesis");
+    assert_eq!(
+        chain,
+        LocalChain::from_blocks(blocks.into_iter().collect()).expect("must have genesis")
+    );
+    assert_eq!(chain.tip().block_id(), block_id!(3, "C"));
+
+    assert_eq!(
+        LocalChain::from_blocks_sorted(vec![(1, h!("A"))]),
+        Err(FromBlocksSortedError::MissingGenesis(MissingGenesisError))
+    );
+    assert_eq!(
+        LocalChain::from_blocks_sorted(vec![]),
+        Err(FromBlocksSortedError::MissingGenesis(MissingGenesisError))
+    );
+
+    // unsorted and duplicate heights are rejected
+    assert_eq!(
+        LocalChain::from_blocks_sorted(vec![(0, h!("_")), (3, h!("C")), (1, h!("A"))]),
+        Err(FromBlocksSortedError::NotAscending { height: 1 })
+    );
+    assert_eq!(
+        LocalChain::from_blocks_sorted(vec![(0, h!("_")), (1, h!("A")), (1, h!("A'"))]),
+        Err(FromBlocksSortedError::NotAscending { height: 1 })
+    );
+}
+
+#[test]
+fn local_chain_tip_ancestor_at() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C")), (4, h!("D"))];
+    assert_eq!(chain.tip_ancestor_at(0), Some(block_id!(4, "D")));
+    assert_eq!(chain.tip_ancestor_at(1), Some(block_id!(3, "C")));
+    assert_eq!(chain.tip_ancestor_at(2), None);
+    assert_eq!(chain.tip_ancestor_at(4), Some(block_id!(0, "_")));
+    assert_eq!(chain.tip_ancestor_at(5), None);
+}
+
+#[test]
+fn local_chain_suggest_connection_block() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    let mut update_blocks = vec![block_id!(0, "_"), block_id!(3, "C")];
+
+    // keep including the suggested block until the update connects
+    let mut suggested = Vec::new();
+    let changeset = loop {
+        let update = CheckPoint::from_block_ids(update_blocks.clone())
+            .expect("must be in order")
+            .into_update(true);
+        match chain.apply_update(update) {
+            Ok(changeset) => break changeset,
+            Err(err) => {
+                let block = chain
+                    .suggest_connection_block(&err)
+                    .expect("must suggest a block");
+                suggested.push(block);
+                update_blocks.push(block);
+                update_blocks.sort();
+            }
+        }
+    };
+    assert_eq!(suggested, vec![block_id!(1, "A"), block_id!(2, "B")]);
+    assert_eq!(changeset, [(3, Some(h!("C")))].into());
+
+    let err = CannotConnectError {
+        try_include_height: 5,
+    };
+    assert_eq!(chain.suggest_connection_block(&err), None);
+}
+
+#[test]
+fn local_chain_common_height() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (4, h!("D"))];
+
+    let other = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+    assert_eq!(chain.common_height(&other), Some(2));
+    assert_eq!(other.common_height(&chain), Some(2));
+
+    let other = local_chain![(0, h!("_")), (2, h!("B'")), (4, h!("D'"))];
+    assert_eq!(chain.common_height(&other), Some(0));
+
+    let other = local_chain![(0, h!("other_genesis")), (1, h!("A"))];
+    assert_eq!(chain.common_height(&other), None);
+
+    assert_eq!(chain.common_height(&chain), Some(4));
+}
+
+#[test]
+fn checkpoint_is_competing_fork() {
+    let tip = CheckPoint::new(block_id!(1, "A"));
+    assert!(tip.is_competing_fork(&CheckPoint::new(block_id!(1, "A'"))));
+    assert!(!tip.is_competing_fork(&tip));
+    assert!(!tip.is_competing_fork(&CheckPoint::new(block_id!(1, "A"))));
+    // checkpoints at different heights are never competing forks
+    assert!(!tip.is_competing_fork(&CheckPoint::new(block_id!(2, "B"))));
+
+    // only the checkpoints themselves are compared, not their history
+    let fork_tip = local_chain![(0, h!("_")), (1, h!("A"))].tip();
+    assert!(!tip.is_competing_fork(&fork_tip));
+}
+
+#[test]
+fn local_chain_apply_rollback() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+
+    // a new tip which does not fork off the chain does not roll anything back
+    let new_tip = local_chain![(0, h!("_")), (1, h!("A"))].tip();
+    assert_eq!(chain.apply_rollback(new_tip), Ok(ChangeSet::default()));
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))]
+    );
+
+    let new_tip = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B'"))].tip();
+    assert_eq!(
+        chain.apply_rollback(new_tip),
+        Ok([(2, Some(h!("B'"))), (3, None)].into())
+    );
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B'"))]
+    );
+}
+
+#[test]
+fn block_id_from_block() {
+    let block = bitcoin::constants::genesis_block(bitcoin::Network::Testnet);
+    let block_id = BlockId::from_block(&block, 0);
+    assert_eq!(
+        block_id,
+        BlockId {
+            height: 0,
+            hash: block.block_hash(),
+        }
+    );
+    assert_eq!(BlockId::from((&block, 0)), block_id);
+
+    let chain = LocalChain::new_from_genesis(block.block_hash());
+    assert_eq!(chain.tip().block_id(), block_id);
+
+    // the given height is used as is
+    assert_eq!(BlockId::from_block(&block, 5), block_id.with_height(5));
+}
+
+#[test]
+fn local_chain_apply_update_max_height() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A"))];
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (1_000_000, h!("Z"))];
+    assert_eq!(
+        chain.apply_update_max_height(update, 100),
+        Err(ApplyUpdateError::TipTooHigh { height: 1_000_000 })
+    );
+    assert_eq!(chain, local_chain![(0, h!("_")), (1, h!("A"))]);
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (100, h!("B"))];
+    assert_eq!(
+        chain.apply_update_max_height(update, 100),
+        Ok([(100, Some(h!("B")))].into())
+    );
+}
+
+#[test]
+fn local_chain_take_staged() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A"))];
+
+    // staging is opt-in
+    chain
+        .apply_update(chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))])
+        .expect("must apply");
+    assert_eq!(chain.take_staged(), ChangeSet::default());
+
+    chain.enable_staging();
+    let changeset_1 = chain
+        .apply_update(chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C"))])
+        .expect("must apply");
+    let changeset_2 = chain
+        .apply_update(chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C'"))])
+        .expect("must apply");
+    assert_eq!(changeset_1, [(3, Some(h!("C")))].into());
+    assert_eq!(changeset_2, [(3, Some(h!("C'")))].into());
+    chain.insert_block(block_id!(4, "D")).expect("must insert");
+
+    assert_eq!(
+        chain.take_staged(),
+        [(3, Some(h!("C'"))), (4, Some(h!("D")))].into()
+    );
+    assert_eq!(chain.take_staged(), ChangeSet::default());
+
+    // the staged changes replay onto the chain they were taken from
+    let mut replica = chain.clone();
+    chain.insert_block(block_id!(5, "E")).expect("must insert");
+    replica
+        .apply_changeset(&chain.take_staged())
+        .expect("must apply");
+    assert_eq!(replica, chain);
+}
+
+#[test]
+fn checkpoint_bottoms_out_at() {
+    let genesis = block_id!(0, "_");
+    let full = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))].tip();
+    assert!(full.bottoms_out_at(genesis));
+    assert!(!full.bottoms_out_at(block_id!(0, "other network")));
+    assert!(!full.bottoms_out_at(block_id!(1, "A")));
+
+    let truncated =
+        CheckPoint::from_block_ids([block_id!(1, "A"), block_id!(3, "C")]).expect("must construct");
+    assert!(!truncated.bottoms_out_at(genesis));
+    assert!(truncated.bottoms_out_at(block_id!(1, "A")));
+
+    assert!(CheckPoint::new(genesis).bottoms_out_at(genesis));
+}
+
+#[test]
+fn local_chain_apply_update_split() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D"))];
+    let (added, removed) = chain.apply_update_split(update).expect("must apply");
+    assert_eq!(added, [(2, Some(h!("B'"))), (4, Some(h!("D")))].into());
+    assert_eq!(removed, [(3, None)].into());
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D"))]
+    );
+
+    let update = chain_update![(0, h!("_")), (4, h!("D")), (5, h!("E"))];
+    assert_eq!(
+        chain.apply_update_split(update),
+        Ok(([(5, Some(h!("E")))].into(), ChangeSet::default()))
+    );
+
+    let update = chain_update![(0, h!("_")), (5, h!("E'"))];
+    assert!(chain.apply_update_split(update).is_err());
+}
+
+#[test]
+fn local_chain_height_and_hash_exists() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+
+    assert!(chain.height_exists(0));
+    assert!(chain.height_exists(3));
+    assert!(!chain.height_exists(2));
+    assert!(!chain.height_exists(4));
+
+    assert!(chain.hash_exists(h!("_")));
+    assert!(chain.hash_exists(h!("C")));
+    assert!(!chain.hash_exists(h!("B")));
+}
+
+#[test]
+fn local_chain_trusted_checkpoints() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A"))];
+    chain.set_trusted_checkpoints([(2, h!("B")), (4, h!("D"))].into());
+
+    let violation = TrustedCheckpointViolation {
+        height: 2,
+        expected: h!("B"),
+        got: h!("B'"),
+    };
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))];
+    assert_eq!(
+        chain.apply_update_checked(update),
+        Err(ApplyUpdateError::TrustedCheckpointViolation(
+            violation.clone()
+        ))
+    );
+    assert_eq!(
+        chain.apply_changeset(&[(2, Some(h!("B'")))].into()),
+        Err(ApplyChangeSetError::TrustedCheckpointViolation(
+            violation.clone()
+        ))
+    );
+    assert_eq!(chain, local_chain![(0, h!("_")), (1, h!("A"))]);
+
+    // methods which cannot report the violation itself still refuse the change
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))];
+    assert_eq!(
+        chain.apply_update(update),
+        Err(CannotConnectError {
+            try_include_height: 2
+        })
+    );
+    assert_eq!(
+        chain.insert_block(block_id!(2, "B'")),
+        Err(AlterCheckPointError {
+            height: 2,
+            original_hash: h!("B"),
+            update_hash: Some(h!("B'")),
+        })
+    );
+    assert_eq!(
+        chain.replace_block(block_id!(2, "B'")),
+        Err(AlterCheckPointError {
+            height: 2,
+            original_hash: h!("B"),
+            update_hash: Some(h!("B'")),
+        })
+    );
+    assert_eq!(
+        chain.replace_subchain(block_id!(1, "A"), [block_id!(2, "B'")]),
+        Err(ReplaceError::TrustedCheckpointViolation(violation))
+    );
+    assert_eq!(
+        chain.ensure_heights([block_id!(2, "B'")], |_| Some(h!("B'"))),
+        ChangeSet::default()
+    );
+    assert_eq!(chain, local_chain![(0, h!("_")), (1, h!("A"))]);
+
+    // updates that agree with (or skip) the trusted checkpoints are applied
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+    assert_eq!(
+        chain.apply_update_checked(update),
+        Ok([(2, Some(h!("B"))), (3, Some(h!("C")))].into())
+    );
+    assert_eq!(chain.apply_changeset(&[(5, Some(h!("E")))].into()), Ok(()));
+    assert_eq!(
+        chain.insert_block(block_id!(4, "D")),
+        Ok([(4, Some(h!("D")))].into())
+    );
+}
+
+#[test]
+fn local_chain_reindex_from_tip() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    let blocks = chain.blocks().clone();
+
+    chain.reindex_from_tip();
+    assert_eq!(chain.blocks(), &blocks);
+    assert!(chain.integrity_report().is_ok());
+}
+
+#[test]
+fn checkpoint_iter_block_ids() {
+    let tip = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))].tip();
+    assert_eq!(
+        tip.iter_block_ids().collect::<Vec<_>>(),
+        vec![block_id!(3, "C"), block_id!(1, "A"), block_id!(0, "_")]
+    );
+    assert_eq!(
+        tip.iter_block_ids().collect::<Vec<_>>(),
+        tip.iter().map(|cp| cp.block_id()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn local_chain_apply_update_from_birthday() {
+    let mut chain = local_chain![(0, h!("_"))];
+
+    let update = chain_update![(0, h!("_")), (10, h!("J")), (20, h!("T")), (30, h!("AD"))];
+    assert_eq!(
+        chain.apply_update_from_birthday(update, 20),
+        Ok([(20, Some(h!("T"))), (30, Some(h!("AD")))].into())
+    );
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (20, h!("T")), (30, h!("AD"))]
+    );
+
+    // the only point of agreement is below the birthday
+    let update = chain_update![(0, h!("_")), (10, h!("J")), (30, h!("AD'"))];
+    assert!(chain.apply_update_from_birthday(update, 20).is_err());
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (20, h!("T")), (30, h!("AD"))]
+    );
+}
+
+#[test]
+fn local_chain_apply_stale_update() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C")), (4, h!("D"))];
+
+    // the update is behind the chain, but consistent with it
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (3, h!("C"))];
+    assert_eq!(chain.apply_update(update), Ok(ChangeSet::default()));
+
+    // the update's tip is in the chain, but older blocks are introduced
+    let update = chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C"))];
+    assert_eq!(chain.apply_update(update), Ok([(2, Some(h!("B")))].into()));
+    assert_eq!(
+        chain,
+        local_chain![
+            (0, h!("_")),
+            (1, h!("A")),
+            (2, h!("B")),
+            (3, h!("C")),
+            (4, h!("D"))
+        ]
+    );
+}
+
+#[test]
+fn checkpoint_try_extend() {
+    let base = CheckPoint::new(block_id!(0, "_"));
+
+    let tip = base
+        .clone()
+        .try_extend([block_id!(1, "A"), block_id!(3, "C")])
+        .expect("must extend");
+    assert_eq!(tip.block_id(), block_id!(3, "C"));
+
+    let (partial, failed) = base
+        .try_extend([block_id!(1, "A"), block_id!(2, "B"), block_id!(2, "B'")])
+        .expect_err("must fail on duplicate height");
+    assert_eq!(failed, block_id!(2, "B'"));
+    assert_eq!(
+        partial.iter_block_ids().collect::<Vec<_>>(),
+        vec![block_id!(2, "B"), block_id!(1, "A"), block_id!(0, "_")]
+    );
+}
+
+#[test]
+fn update_genesis_only_local_chain() {
+    let without_older_blocks = |update: Update| Update {
+        introduce_older_blocks: false,
+        ..update
+    };
+    let genesis_only = local_chain![(0, h!("_"))];
+
+    [
+        TestLocalChain {
+            name: "genesis only update",
+            chain: genesis_only.clone(),
+            update: chain_update![(0, h!("_"))],
+            exp: ExpectedResult::Ok {
+                changeset: &[],
+                init_changeset: &[(0, Some(h!("_")))],
+            },
+        },
+        TestLocalChain {
+            name: "sparse update",
+            chain: genesis_only.clone(),
+            update: chain_update![(0, h!("_")), (5, h!("E")), (100, h!("Z"))],
+            exp: ExpectedResult::Ok {
+                changeset: &[(5, Some(h!("E"))), (100, Some(h!("Z")))],
+                init_changeset: &[(0, Some(h!("_"))), (5, Some(h!("E"))), (100, Some(h!("Z")))],
+            },
+        },
+        TestLocalChain {
+            name: "contiguous update",
+            chain: genesis_only.clone(),
+            update: chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+            exp: ExpectedResult::Ok {
+                changeset: &[(1, Some(h!("A"))), (2, Some(h!("B")))],
+                init_changeset: &[(0, Some(h!("_"))), (1, Some(h!("A"))), (2, Some(h!("B")))],
+            },
+        },
+        TestLocalChain {
+            name: "sparse update without introducing older blocks",
+            chain: genesis_only.clone(),
+            update: without_older_blocks(chain_update![(0, h!("_")), (5, h!("E"))]),
+            exp: ExpectedResult::Ok {
+                changeset: &[(5, Some(h!("E")))],
+                init_changeset: &[(0, Some(h!("_"))), (5, Some(h!("E")))],
+            },
+        },
+        TestLocalChain {
+            name: "contiguous update without introducing older blocks",
+            chain: genesis_only.clone(),
+            update: without_older_blocks(chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))]),
+            exp: ExpectedResult::Ok {
+                changeset: &[(1, Some(h!("A"))), (2, Some(h!("B")))],
+                init_changeset: &[(0, Some(h!("_"))), (1, Some(h!("A"))), (2, Some(h!("B")))],
+            },
+        },
+    ]
+    .into_iter()
+    .for_each(TestLocalChain::run);
+}
+
+#[test]
+fn checkpoint_genesis() {
+    let tip = local_chain![(0, h!("_")), (1, h!("A")), (3, h!("C"))].tip();
+    assert_eq!(tip.genesis().block_id(), block_id!(0, "_"));
+    assert_eq!(tip.genesis().prev(), None);
+
+    let partial =
+        CheckPoint::from_block_ids([block_id!(2, "B"), block_id!(3, "C")]).expect("must construct");
+    assert_eq!(partial.genesis().block_id(), block_id!(2, "B"));
+}
+
+#[test]
+fn local_chain_ensure_heights() {
+    let mut chain = local_chain![(0, h!("_")), (2, h!("B"))];
+    let resolve = |height: u32| match height {
+        1 => Some(h!("A")),
+        4 => Some(h!("D")),
+        _ => None,
+    };
+
+    let anchors = [
+        block_id!(2, "B"),
+        block_id!(4, "D"),
+        block_id!(1, "A"),
+        block_id!(4, "D"),
+        block_id!(5, "unresolved"),
+    ];
+    assert_eq!(
+        chain.ensure_heights(anchors, resolve),
+        [(1, Some(h!("A"))), (4, Some(h!("D")))].into()
+    );
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (4, h!("D"))]
+    );
+    assert_eq!(chain.ensure_heights(anchors, resolve), ChangeSet::default());
+}
+
+#[test]
+fn block_id_cmp_height() {
+    use core::cmp::Ordering;
+
+    let block = block_id!(1, "A");
+    let competing_block = block_id!(1, "A'");
+    // competing blocks at the same height are equal by height, but not by `Ord`
+    assert_eq!(block.cmp_height(&competing_block), Ordering::Equal);
+    assert_ne!(block.cmp(&competing_block), Ordering::Equal);
+    assert_eq!(block.cmp_height(&block), Ordering::Equal);
+
+    // the hash is ignored
+    assert_eq!(block.cmp_height(&block_id!(2, "A")), Ordering::Less);
+    assert_eq!(block.cmp_height(&block_id!(0, "A")), Ordering::Greater);
+    assert_eq!(
+        block.cmp_height(&block.with_height(u32::MAX)),
+        Ordering::Less
+    );
+}
+
+#[test]
+fn update_local_chain_with_lower_tip() {
+    let chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+
+    [
+        TestLocalChain {
+            name: "update forks below the tip and invalidates it",
+            chain: chain.clone(),
+            update: chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))],
+            exp: ExpectedResult::Ok {
+                changeset: &[(2, Some(h!("B'"))), (3, None)],
+                init_changeset: &[(0, Some(h!("_"))), (1, Some(h!("A"))), (2, Some(h!("B'")))],
+            },
+        },
+        TestLocalChain {
+            name: "update is a strict subset of the chain",
+            chain: chain.clone(),
+            update: chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))],
+            exp: ExpectedResult::Ok {
+                changeset: &[],
+                init_changeset: &[
+                    (0, Some(h!("_"))),
+                    (1, Some(h!("A"))),
+                    (2, Some(h!("B"))),
+                    (3, Some(h!("C"))),
+                ],
+            },
+        },
+    ]
+    .into_iter()
+    .for_each(TestLocalChain::run);
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'"))];
+    assert_eq!(
+        chain.report_update(&update),
+        Ok(UpdateReport {
+            changeset: [(2, Some(h!("B'"))), (3, None)].into(),
+            would_reorg: true,
+        })
+    );
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    assert_eq!(
+        chain.report_update(&update),
+        Ok(UpdateReport {
+            changeset: ChangeSet::default(),
+            would_reorg: false,
+        })
+    );
+    let update = chain_update![(0, h!("_")), (3, h!("C")), (4, h!("D"))];
+    assert_eq!(
+        chain.report_update(&update),
+        Ok(UpdateReport {
+            changeset: [(4, Some(h!("D")))].into(),
+            would_reorg: false,
+        })
+    );
+}
+
+#[test]
+fn local_chain_fast_forward() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (3, h!("C"))];
+    assert_eq!(
+        chain.fast_forward(update),
+        Err(FastForwardError::WouldReorg { height: 2 })
+    );
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))]
+    );
+
+    let update = chain_update![(0, h!("_")), (3, h!("C"))];
+    assert!(matches!(
+        chain.fast_forward(update),
+        Err(FastForwardError::CannotConnect(_))
+    ));
+
+    let update = chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C"))];
+    assert_eq!(chain.fast_forward(update), Ok([(3, Some(h!("C")))].into()));
+    assert_eq!(
+        chain,
+        local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))]
+    );
+}
+
+#[test]
+fn local_chain_update_changeset_can_be_replayed() {
+    let chain = local_chain![(0, h!("_"))];
+
+    // an update with a different genesis is refused rather than creating a changeset which
+    // `apply_changeset` would reject
+    let mut updated = chain.clone();
+    let update = CheckPoint::new(block_id!(0, "X")).into_update(false);
+    assert_eq!(
+        updated.apply_update(update),
+        Err(CannotConnectError {
+            try_include_height: 0
+        })
+    );
+    assert_eq!(updated, chain);
+
+    let update = chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+    let changeset = updated.apply_update(update).expect("update must connect");
+    let mut replayed = chain.clone();
+    assert_eq!(replayed.apply_changeset(&changeset), Ok(()));
+    assert_eq!(replayed, updated);
+}
+
+#[test]
+fn local_chain_apply_block_ids_not_ascending() {
+    let mut chain = local_chain![(0, h!("_")), (1, h!("A"))];
+
+    for blocks in [
+        vec![block_id!(0, "_"), block_id!(2, "B"), block_id!(2, "B'")],
+        vec![block_id!(0, "_"), block_id!(3, "C"), block_id!(2, "B")],
+    ] {
+        assert_eq!(
+            chain.apply_block_ids(blocks.into_iter()),
+            Err(ApplyBlockIdsError::NotAscending { height: 2 })
+        );
+        assert_eq!(chain, local_chain![(0, h!("_")), (1, h!("A"))]);
+    }
+}
+
+#[test]
+fn local_chain_apply_update_returning_prev() {
+    let original = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B"))];
+
+    let mut chain = original.clone();
+    let update = chain_update![(0, h!("_")), (2, h!("B")), (3, h!("C"))];
+    let (changeset, prev_tip) = chain
+        .apply_update_returning_prev(update)
+        .expect("update must connect");
+    assert_eq!(changeset, [(3, Some(h!("C")))].into());
+    assert_eq!(prev_tip.block_id(), block_id!(2, "B"));
+    assert!(prev_tip.is_tip_of(&original));
+    assert_eq!(chain.tip().block_id(), block_id!(3, "C"));
+
+    // an update that cannot connect leaves the chain unchanged
+    let mut chain = original.clone();
+    let update = chain_update![(0, h!("_")), (3, h!("C"))];
+    assert_eq!(
+        chain.apply_update_returning_prev(update),
+        Err(CannotConnectError {
+            try_include_height: 1
+        })
+    );
+    assert_eq!(chain, original);
+}
+
+#[test]
+fn local_chain_compute_merge_changeset() {
+    let original = local_chain![(0, h!("_")), (1, h!("A")), (2, h!("B")), (3, h!("C"))];
+
+    for update in [
+        chain_update![(0, h!("_")), (1, h!("A")), (2, h!("B'")), (4, h!("D"))],
+        chain_update![(0, h!("_")), (3, h!("C")), (4, h!("D"))],
+        chain_update![(0, h!("_")), (4, h!("D"))],
+    ] {
+        let chain = original.clone();
+        let changeset =
+            compute_merge_changeset(&chain.tip(), &update.tip, update.introduce_older_blocks);
+        assert_eq!(chain, original);
+
+        let mut exp_chain = original.clone();
+        assert_eq!(changeset, exp_chain.apply_update(update));
+    }
+}